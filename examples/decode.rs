@@ -62,7 +62,7 @@ fn main() {
 
     let max_packet = 1500;
     let max_frame = 48000 * 2;
-    let max_frame_samples = max_frame * dec_opt.channels as usize;
+    let max_frame_samples = max_frame * dec_opt.channels;
 
     let mut pkt = Vec::with_capacity(max_packet);
     let mut samples = Vec::with_capacity(max_frame_samples);
@@ -83,7 +83,7 @@ fn main() {
         if let Ok(ret) = dec.decode(&pkt[..len], &mut samples[..], false) {
             // Write the actual group of samples
             let out =
-                unsafe { slice::from_raw_parts(samples.as_ptr() as *const u8, ret as usize * 2) };
+                unsafe { slice::from_raw_parts(samples.as_ptr() as *const u8, ret * 2) };
             out_f.write_all(out).unwrap();
         } else {
             panic!("Cannot decode");
@@ -66,7 +66,7 @@ fn main() {
     let mut out_f = File::create(enc_opt.output).unwrap();
 
     let frame_size = 2880;
-    let total_bytes = (enc_opt.channels * enc_opt.seconds * enc_opt.sampling_rate * 2) as usize;
+    let total_bytes = enc_opt.channels * enc_opt.seconds * enc_opt.sampling_rate * 2;
     let max_packet = 1500;
     let mut processed_bytes = 0;
     let mut buf = Vec::with_capacity(frame_size * 2);
@@ -1,11 +1,17 @@
+#[cfg(feature = "bindgen")]
 extern crate bindgen;
+extern crate cc;
 extern crate metadeps;
 
-use std::path::PathBuf;
-use std::io::Write;
+#[cfg(any(feature = "bindgen", feature = "dlopen"))]
 use std::env;
+#[cfg(feature = "bindgen")]
 use std::fs::File;
+#[cfg(feature = "bindgen")]
+use std::io::Write;
+use std::path::PathBuf;
 
+#[cfg(feature = "bindgen")]
 fn format_write(builder: bindgen::Builder) -> String {
     builder
         .generate()
@@ -15,22 +21,367 @@ fn format_write(builder: bindgen::Builder) -> String {
         .replace("/*!", "/*")
 }
 
-fn main() {
-    let libs = metadeps::probe().unwrap();
-    let headers = libs.get("opus").unwrap().include_paths.clone();
+/// Build the bundled libopus sources and link them statically.
+///
+/// The source lists are read from the automake fragments shipped with
+/// libopus, so updating the bundled copy does not require touching this file.
+#[cfg(all(feature = "vendored", not(feature = "dlopen")))]
+mod vendored {
+    use std::env;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    pub const VERSION: &str = "1.3.1";
+
+    /// The version string suffix libopus reports, e.g. `1.3.1-fixed`.
+    pub fn version() -> String {
+        if cfg!(feature = "fixed-point") {
+            format!("{}-fixed", VERSION)
+        } else {
+            VERSION.to_owned()
+        }
+    }
+
+    /// The SILK source group of the selected arithmetic.
+    fn silk(group: &str) -> String {
+        if cfg!(feature = "fixed-point") {
+            format!("{}_FIXED", group)
+        } else {
+            format!("{}_FLOAT", group)
+        }
+    }
+
+    /// Parse `NAME = a.c b.c` assignments out of a `*_sources.mk` file.
+    fn sources(root: &Path, mk: &str, name: &str) -> Vec<PathBuf> {
+        let path = root.join(mk);
+        let text = fs::read_to_string(&path)
+            .unwrap_or_else(|e| panic!("cannot read {}: {}", path.display(), e));
+        let text = text.replace("\\\n", " ");
+
+        text.lines()
+            .filter_map(|line| {
+                let mut parts = line.splitn(2, '=');
+                let lhs = parts.next()?.trim();
+                let rhs = parts.next()?;
+                if lhs == name {
+                    Some(rhs)
+                } else {
+                    None
+                }
+            })
+            .flat_map(|rhs| rhs.split_whitespace())
+            .map(|file| root.join(file))
+            .collect()
+    }
+
+    fn base(root: &Path) -> cc::Build {
+        let target = env::var("TARGET").unwrap();
+        let mut build = cc::Build::new();
+
+        build
+            .include(root.join("include"))
+            .include(root.join("celt"))
+            .include(root.join("silk"))
+            .include(root)
+            .define("OPUS_BUILD", None)
+            .define("PACKAGE_VERSION", Some(format!("\"{}\"", VERSION).as_str()))
+            .define("HAVE_LRINT", None)
+            .define("HAVE_LRINTF", None)
+            .warnings(false);
+
+        if cfg!(feature = "fixed-point") {
+            build
+                .include(root.join("silk/fixed"))
+                .define("FIXED_POINT", None);
+        } else {
+            build.include(root.join("silk/float"));
+        }
+
+        if cfg!(feature = "custom-modes") {
+            build.define("CUSTOM_MODES", None);
+        }
+
+        if target.contains("msvc") {
+            build.define("USE_ALLOCA", None);
+        } else {
+            build.define("VAR_ARRAYS", None);
+        }
+
+        let arch = env::var("CARGO_CFG_TARGET_ARCH").unwrap();
+        match arch.as_str() {
+            "x86" | "x86_64" => {
+                build
+                    .define("OPUS_HAVE_RTCD", None)
+                    .define("CPU_INFO_BY_C", None)
+                    .define("OPUS_X86_MAY_HAVE_SSE", None)
+                    .define("OPUS_X86_MAY_HAVE_SSE2", None)
+                    .define("OPUS_X86_MAY_HAVE_SSE4_1", None);
+                if arch == "x86_64" {
+                    build
+                        .define("OPUS_X86_PRESUME_SSE", None)
+                        .define("OPUS_X86_PRESUME_SSE2", None);
+                }
+            }
+            "aarch64" => {
+                build
+                    .define("OPUS_ARM_MAY_HAVE_NEON_INTR", None)
+                    .define("OPUS_ARM_PRESUME_NEON_INTR", None)
+                    .define("OPUS_ARM_PRESUME_AARCH64_NEON_INTR", None);
+            }
+            _ => {}
+        }
+
+        build
+    }
+
+    /// Compile a group of sources that need extra instruction set flags.
+    fn intrinsics(root: &Path, files: &[PathBuf], flags: &[&str]) -> Vec<PathBuf> {
+        let mut build = base(root);
+        build.files(files);
+        for flag in flags {
+            build.flag_if_supported(flag);
+        }
+        build.compile_intermediates()
+    }
+
+    pub fn build() -> Vec<PathBuf> {
+        if cfg!(feature = "dred") {
+            panic!(
+                "\n\nThe `dred` feature needs libopus >= 1.5 and cannot be combined with \
+                 `vendored` or `fixed-point`, the bundled sources are libopus {}.\n\n",
+                VERSION
+            );
+        }
+
+        let root = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap()).join("opus");
+
+        if !root.join("include/opus.h").exists() {
+            panic!(
+                "the bundled libopus sources are missing from {}; \
+                 disable the `vendored` feature to use the system library",
+                root.display()
+            );
+        }
+
+        println!("cargo:rerun-if-changed={}", root.display());
+
+        let mut files = sources(&root, "celt_sources.mk", "CELT_SOURCES");
+        files.extend(sources(&root, "silk_sources.mk", "SILK_SOURCES"));
+        files.extend(sources(&root, "silk_sources.mk", &silk("SILK_SOURCES")));
+        files.extend(sources(&root, "opus_sources.mk", "OPUS_SOURCES"));
+        files.extend(sources(&root, "opus_sources.mk", "OPUS_SOURCES_FLOAT"));
+
+        let mut objects = Vec::new();
+
+        match env::var("CARGO_CFG_TARGET_ARCH").unwrap().as_str() {
+            "x86" | "x86_64" => {
+                let sse = sources(&root, "celt_sources.mk", "CELT_SOURCES_SSE");
+                let sse2 = sources(&root, "celt_sources.mk", "CELT_SOURCES_SSE2");
+                let mut sse4_1 = sources(&root, "celt_sources.mk", "CELT_SOURCES_SSE4_1");
+                sse4_1.extend(sources(&root, "silk_sources.mk", "SILK_SOURCES_SSE4_1"));
+                if cfg!(feature = "fixed-point") {
+                    sse4_1.extend(sources(
+                        &root,
+                        "silk_sources.mk",
+                        "SILK_SOURCES_FIXED_SSE4_1",
+                    ));
+                }
 
-    let mut builder = bindgen::builder().header("data/opus.h");
+                objects.extend(intrinsics(&root, &sse, &["-msse"]));
+                objects.extend(intrinsics(&root, &sse2, &["-msse2"]));
+                objects.extend(intrinsics(&root, &sse4_1, &["-msse4.1"]));
+            }
+            "aarch64" => {
+                files.extend(sources(&root, "celt_sources.mk", "CELT_SOURCES_ARM"));
+                files.extend(sources(
+                    &root,
+                    "celt_sources.mk",
+                    "CELT_SOURCES_ARM_NEON_INTR",
+                ));
+                files.extend(sources(
+                    &root,
+                    "silk_sources.mk",
+                    "SILK_SOURCES_ARM_NEON_INTR",
+                ));
+                if cfg!(feature = "fixed-point") {
+                    files.extend(sources(
+                        &root,
+                        "silk_sources.mk",
+                        "SILK_SOURCES_FIXED_ARM_NEON_INTR",
+                    ));
+                }
+            }
+            _ => {}
+        }
+
+        base(&root).files(&files).objects(&objects).compile("opus");
+
+        vec![root.join("include")]
+    }
+}
+
+#[cfg(all(feature = "dlopen", feature = "vendored"))]
+compile_error!(
+    "The `dlopen` feature opens the system libopus at runtime and cannot be \
+     combined with `vendored` or `fixed-point`, which build the bundled one."
+);
+
+/// Link libopus, returning its include paths and version if known.
+#[cfg(all(feature = "vendored", not(feature = "dlopen")))]
+fn link() -> (Vec<PathBuf>, Option<String>) {
+    (vendored::build(), Some(vendored::version()))
+}
+
+/// Nothing is linked, the library is opened at runtime.
+#[cfg(feature = "dlopen")]
+fn link() -> (Vec<PathBuf>, Option<String>) {
+    let include = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap()).join("opus/include");
+
+    (vec![include], None)
+}
+
+#[cfg(not(any(feature = "vendored", feature = "dlopen")))]
+fn link() -> (Vec<PathBuf>, Option<String>) {
+    let libs = metadeps::probe().unwrap_or_else(|e| {
+        panic!(
+            "\n\nlibopus >= 1.3 could not be found through pkg-config:\n\n{}\n\n\
+             Install the libopus development package (e.g. libopus-dev) and make sure \
+             PKG_CONFIG_PATH points to its opus.pc, or enable the `vendored` feature \
+             to build the bundled libopus sources.\n\n",
+            e
+        )
+    });
+
+    let opus = libs.get("opus").unwrap();
+
+    (opus.include_paths.clone(), Some(opus.version.clone()))
+}
+
+/// Releases that get an `opus_X_Y` cfg flag, and an `OPUS_RS_X_Y` define in
+/// the ctl shim, once the API level reaches them.
+const RELEASES: &[(u32, u32)] = &[(1, 4), (1, 5)];
+
+/// Expose the linked version to the crate as the `OPUS_SYS_LINKED_VERSION`
+/// environment variable and to dependents as `DEP_OPUS_VERSION`, returning
+/// the API level to bind.
+///
+/// The version of a library opened at runtime is unknown, the bundled 1.3
+/// headers are bound unless the `dred` feature asks for the 1.5 API.
+fn linked_version(version: Option<&str>) -> (u32, u32) {
+    let version = match version {
+        Some(version) => version,
+        None if cfg!(feature = "dred") => return (1, 5),
+        None => return (1, 3),
+    };
+
+    let mut parts = version
+        .split(|c: char| !c.is_ascii_digit())
+        .map(|n| n.parse::<u32>().unwrap_or(0));
+    let linked = (parts.next().unwrap_or(0), parts.next().unwrap_or(0));
+
+    if cfg!(feature = "dred") && linked < (1, 5) {
+        panic!(
+            "\n\nThe `dred` feature needs libopus >= 1.5, found {}.\n\n",
+            version
+        );
+    }
+
+    println!("cargo:rustc-env=OPUS_SYS_LINKED_VERSION={}", version);
+    println!("cargo:version={}", version);
+
+    linked
+}
+
+/// Emit the `opus_X_Y` cfg flag of every release up to `level`.
+fn release_cfg(level: (u32, u32)) {
+    for &(major, minor) in RELEASES {
+        println!("cargo::rustc-check-cfg=cfg(opus_{}_{})", major, minor);
+        if level >= (major, minor) {
+            println!("cargo::rustc-cfg=opus_{}_{}", major, minor);
+        }
+    }
+}
+
+/// Regenerate the bindings into `OUT_DIR/opus.rs` and `OUT_DIR/functions.rs`.
+///
+/// The output is meant to be copied over `src/bindings.rs` and
+/// `src/functions.rs` whenever the supported libopus API changes. The
+/// functions are kept apart so the `dlopen` build can replace them.
+#[cfg(feature = "bindgen")]
+fn generate(headers: Vec<PathBuf>) {
+    let mut builder = bindgen::builder()
+        .header("data/opus.h")
+        .generate_comments(false);
 
     for header in headers {
         builder = builder.clang_arg("-I").clang_arg(header.to_str().unwrap());
     }
 
-    // Manually fix the comment so rustdoc won't try to pick them
-    let s = format_write(builder);
+    if cfg!(feature = "custom-modes") {
+        builder = builder.clang_arg("-DOPUS_RS_CUSTOM_MODES");
+    }
+
+    let types = builder
+        .clone()
+        .allowlist_type("Opus.*")
+        .allowlist_type("opus_.*")
+        .allowlist_var("OPUS_.*")
+        .ignore_functions();
+
+    let functions = builder
+        .allowlist_function("opus_.*")
+        .blocklist_type("Opus.*")
+        .blocklist_type("opus_.*");
 
     let out_path = PathBuf::from(env::var("OUT_DIR").unwrap());
 
-    let mut file = File::create(out_path.join("opus.rs")).unwrap();
+    // Manually fix the comment so rustdoc won't try to pick them
+    for (builder, name) in [(types, "opus.rs"), (functions, "functions.rs")] {
+        let s = format_write(builder);
+
+        let mut file = File::create(out_path.join(name)).unwrap();
+
+        let _ = file.write(s.as_bytes());
+    }
+}
+
+#[cfg(not(feature = "bindgen"))]
+fn generate(_headers: Vec<PathBuf>) {}
+
+/// Build the typed ctl wrappers declared in `data/ctl.h`.
+fn shim(headers: &[PathBuf], level: (u32, u32)) {
+    println!("cargo:rerun-if-changed=data/ctl.c");
+    println!("cargo:rerun-if-changed=data/ctl.h");
+
+    let mut build = cc::Build::new();
+
+    if cfg!(feature = "custom-modes") {
+        build.define("OPUS_RS_CUSTOM_MODES", None);
+    }
+    for &(major, minor) in RELEASES {
+        if level >= (major, minor) {
+            build.define(&format!("OPUS_RS_{}_{}", major, minor), None);
+        }
+    }
+    // The ctl entry points are resolved at runtime, see src/dynamic.rs
+    if cfg!(feature = "dlopen") {
+        build.define("OPUS_RS_DLOPEN", None);
+    }
+
+    build
+        .file("data/ctl.c")
+        .includes(headers)
+        .compile("opus_ctl");
+}
+
+fn main() {
+    let (headers, version) = link();
+
+    let level = linked_version(version.as_deref());
+
+    release_cfg(level);
+
+    shim(&headers, level);
 
-    let _ = file.write(s.as_bytes());
+    generate(headers);
 }
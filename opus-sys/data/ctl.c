@@ -0,0 +1,119 @@
+#include "ctl.h"
+
+#ifdef OPUS_RS_DLOPEN
+/*
+ * Nothing is linked in the dlopen build: the loader stores the ctl entry
+ * points of the opened library here before handing out any wrapper, so the
+ * variadic calls are still made from C.
+ */
+#define OPUS_RS_DL_CTL(kind, type) int (*opus_rs_dl_##kind##_ctl)(type *st, int request, ...);
+
+OPUS_RS_DL_CTL(encoder, OpusEncoder)
+OPUS_RS_DL_CTL(decoder, OpusDecoder)
+OPUS_RS_DL_CTL(multistream_encoder, OpusMSEncoder)
+OPUS_RS_DL_CTL(multistream_decoder, OpusMSDecoder)
+OPUS_RS_DL_CTL(projection_encoder, OpusProjectionEncoder)
+OPUS_RS_DL_CTL(projection_decoder, OpusProjectionDecoder)
+
+#define opus_encoder_ctl (*opus_rs_dl_encoder_ctl)
+#define opus_decoder_ctl (*opus_rs_dl_decoder_ctl)
+#define opus_multistream_encoder_ctl (*opus_rs_dl_multistream_encoder_ctl)
+#define opus_multistream_decoder_ctl (*opus_rs_dl_multistream_decoder_ctl)
+#define opus_projection_encoder_ctl (*opus_rs_dl_projection_encoder_ctl)
+#define opus_projection_decoder_ctl (*opus_rs_dl_projection_decoder_ctl)
+
+#ifdef OPUS_RS_CUSTOM_MODES
+OPUS_RS_DL_CTL(custom_encoder, OpusCustomEncoder)
+OPUS_RS_DL_CTL(custom_decoder, OpusCustomDecoder)
+
+#define opus_custom_encoder_ctl (*opus_rs_dl_custom_encoder_ctl)
+#define opus_custom_decoder_ctl (*opus_rs_dl_custom_decoder_ctl)
+#endif
+
+#ifdef OPUS_RS_1_5
+OPUS_RS_DL_CTL(dred_decoder, OpusDREDDecoder)
+
+#define opus_dred_decoder_ctl (*opus_rs_dl_dred_decoder_ctl)
+#endif
+#endif
+
+#define OPUS_RS_DEFINE(kind, type, name, request, arg) \
+    int opus_rs_##kind##_##name(type *st, arg x) \
+    { \
+        return opus_##kind##_ctl(st, request, x); \
+    }
+
+#define OPUS_RS_DEFINE_STATE(kind, type, ctls) \
+    OPUS_RS_GENERIC_CTLS(OPUS_RS_DEFINE, kind, type) \
+    ctls(OPUS_RS_DEFINE, kind, type) \
+    int opus_rs_##kind##_reset_state(type *st) \
+    { \
+        return opus_##kind##_ctl(st, OPUS_RESET_STATE); \
+    }
+
+OPUS_RS_DEFINE_STATE(encoder, OpusEncoder, OPUS_RS_ENCODER_CTLS)
+OPUS_RS_DEFINE_STATE(decoder, OpusDecoder, OPUS_RS_DECODER_CTLS)
+OPUS_RS_DEFINE_STATE(multistream_encoder, OpusMSEncoder, OPUS_RS_ENCODER_CTLS)
+OPUS_RS_DEFINE_STATE(multistream_decoder, OpusMSDecoder, OPUS_RS_DECODER_CTLS)
+OPUS_RS_DEFINE_STATE(projection_encoder, OpusProjectionEncoder, OPUS_RS_ENCODER_CTLS)
+OPUS_RS_DEFINE_STATE(projection_decoder, OpusProjectionDecoder, OPUS_RS_DECODER_CTLS)
+
+#ifdef OPUS_RS_CUSTOM_MODES
+#define OPUS_RS_DEFINE_CUSTOM_STATE(kind, type, ctls) \
+    OPUS_RS_CUSTOM_GENERIC_CTLS(OPUS_RS_DEFINE, kind, type) \
+    ctls(OPUS_RS_DEFINE, kind, type) \
+    int opus_rs_##kind##_reset_state(type *st) \
+    { \
+        return opus_##kind##_ctl(st, OPUS_RESET_STATE); \
+    }
+
+OPUS_RS_DEFINE_CUSTOM_STATE(custom_encoder, OpusCustomEncoder, OPUS_RS_CUSTOM_ENCODER_CTLS)
+OPUS_RS_DEFINE_CUSTOM_STATE(custom_decoder, OpusCustomDecoder, OPUS_RS_CUSTOM_DECODER_CTLS)
+#endif
+
+int opus_rs_multistream_encoder_get_encoder_state(OpusMSEncoder *st, opus_int32 stream_id, OpusEncoder **state)
+{
+    return opus_multistream_encoder_ctl(st, OPUS_MULTISTREAM_GET_ENCODER_STATE(stream_id, state));
+}
+
+int opus_rs_multistream_decoder_get_decoder_state(OpusMSDecoder *st, opus_int32 stream_id, OpusDecoder **state)
+{
+    return opus_multistream_decoder_ctl(st, OPUS_MULTISTREAM_GET_DECODER_STATE(stream_id, state));
+}
+
+int opus_rs_projection_encoder_get_demixing_matrix_gain(OpusProjectionEncoder *st, opus_int32 *x)
+{
+    return opus_projection_encoder_ctl(st, OPUS_PROJECTION_GET_DEMIXING_MATRIX_GAIN(x));
+}
+
+int opus_rs_projection_encoder_get_demixing_matrix_size(OpusProjectionEncoder *st, opus_int32 *x)
+{
+    return opus_projection_encoder_ctl(st, OPUS_PROJECTION_GET_DEMIXING_MATRIX_SIZE(x));
+}
+
+int opus_rs_projection_encoder_get_demixing_matrix(OpusProjectionEncoder *st, unsigned char *matrix, opus_int32 size)
+{
+    return opus_projection_encoder_ctl(st, OPUS_PROJECTION_GET_DEMIXING_MATRIX(matrix, size));
+}
+
+#ifdef OPUS_RS_1_5
+OPUS_RS_DEFINE(encoder, OpusEncoder, set_dred_duration, OPUS_SET_DRED_DURATION_REQUEST, opus_int32)
+OPUS_RS_DEFINE(encoder, OpusEncoder, get_dred_duration, OPUS_GET_DRED_DURATION_REQUEST, opus_int32 *)
+OPUS_RS_DEFINE(decoder, OpusDecoder, set_complexity, OPUS_SET_COMPLEXITY_REQUEST, opus_int32)
+OPUS_RS_DEFINE(decoder, OpusDecoder, get_complexity, OPUS_GET_COMPLEXITY_REQUEST, opus_int32 *)
+
+int opus_rs_encoder_set_dnn_blob(OpusEncoder *st, const void *data, opus_int32 len)
+{
+    return opus_encoder_ctl(st, OPUS_SET_DNN_BLOB(data, len));
+}
+
+int opus_rs_decoder_set_dnn_blob(OpusDecoder *st, const void *data, opus_int32 len)
+{
+    return opus_decoder_ctl(st, OPUS_SET_DNN_BLOB(data, len));
+}
+
+int opus_rs_dred_decoder_set_dnn_blob(OpusDREDDecoder *st, const void *data, opus_int32 len)
+{
+    return opus_dred_decoder_ctl(st, OPUS_SET_DNN_BLOB(data, len));
+}
+#endif
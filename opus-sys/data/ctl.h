@@ -0,0 +1,137 @@
+/*
+ * Typed, non-variadic wrappers around the opus_*_ctl() requests.
+ *
+ * Calling a C variadic function from Rust forces every argument through the
+ * default promotions of the caller, which is easy to get subtly wrong. Each
+ * wrapper forwards exactly one request with the argument type libopus reads.
+ */
+
+#ifndef OPUS_RS_CTL_H
+#define OPUS_RS_CTL_H
+
+#include <opus.h>
+#include <opus_multistream.h>
+#include <opus_projection.h>
+
+#if defined(OPUS_RS_DLOPEN) && defined(OPUS_RS_1_5) && !defined(OPUS_SET_DRED_DURATION_REQUEST)
+/* The dlopen build uses the bundled 1.3 headers, spell out the 1.5 requests */
+#define OPUS_SET_DRED_DURATION_REQUEST 4050
+#define OPUS_GET_DRED_DURATION_REQUEST 4051
+#define OPUS_SET_DNN_BLOB_REQUEST 4052
+#define OPUS_SET_DNN_BLOB(data, len) OPUS_SET_DNN_BLOB_REQUEST, (const void *)(data), (opus_int32)(len)
+typedef struct OpusDREDDecoder OpusDREDDecoder;
+#endif
+
+/* Requests understood by every encoder and decoder state */
+#define OPUS_RS_GENERIC_CTLS(X, kind, type) \
+    X(kind, type, get_bandwidth, OPUS_GET_BANDWIDTH_REQUEST, opus_int32 *) \
+    X(kind, type, get_sample_rate, OPUS_GET_SAMPLE_RATE_REQUEST, opus_int32 *) \
+    X(kind, type, get_final_range, OPUS_GET_FINAL_RANGE_REQUEST, opus_uint32 *) \
+    X(kind, type, set_phase_inversion_disabled, OPUS_SET_PHASE_INVERSION_DISABLED_REQUEST, opus_int32) \
+    X(kind, type, get_phase_inversion_disabled, OPUS_GET_PHASE_INVERSION_DISABLED_REQUEST, opus_int32 *)
+
+#define OPUS_RS_ENCODER_CTLS(X, kind, type) \
+    X(kind, type, set_application, OPUS_SET_APPLICATION_REQUEST, opus_int32) \
+    X(kind, type, get_application, OPUS_GET_APPLICATION_REQUEST, opus_int32 *) \
+    X(kind, type, set_bitrate, OPUS_SET_BITRATE_REQUEST, opus_int32) \
+    X(kind, type, get_bitrate, OPUS_GET_BITRATE_REQUEST, opus_int32 *) \
+    X(kind, type, set_max_bandwidth, OPUS_SET_MAX_BANDWIDTH_REQUEST, opus_int32) \
+    X(kind, type, get_max_bandwidth, OPUS_GET_MAX_BANDWIDTH_REQUEST, opus_int32 *) \
+    X(kind, type, set_vbr, OPUS_SET_VBR_REQUEST, opus_int32) \
+    X(kind, type, get_vbr, OPUS_GET_VBR_REQUEST, opus_int32 *) \
+    X(kind, type, set_bandwidth, OPUS_SET_BANDWIDTH_REQUEST, opus_int32) \
+    X(kind, type, set_complexity, OPUS_SET_COMPLEXITY_REQUEST, opus_int32) \
+    X(kind, type, get_complexity, OPUS_GET_COMPLEXITY_REQUEST, opus_int32 *) \
+    X(kind, type, set_inband_fec, OPUS_SET_INBAND_FEC_REQUEST, opus_int32) \
+    X(kind, type, get_inband_fec, OPUS_GET_INBAND_FEC_REQUEST, opus_int32 *) \
+    X(kind, type, set_packet_loss_perc, OPUS_SET_PACKET_LOSS_PERC_REQUEST, opus_int32) \
+    X(kind, type, get_packet_loss_perc, OPUS_GET_PACKET_LOSS_PERC_REQUEST, opus_int32 *) \
+    X(kind, type, set_dtx, OPUS_SET_DTX_REQUEST, opus_int32) \
+    X(kind, type, get_dtx, OPUS_GET_DTX_REQUEST, opus_int32 *) \
+    X(kind, type, set_vbr_constraint, OPUS_SET_VBR_CONSTRAINT_REQUEST, opus_int32) \
+    X(kind, type, get_vbr_constraint, OPUS_GET_VBR_CONSTRAINT_REQUEST, opus_int32 *) \
+    X(kind, type, set_force_channels, OPUS_SET_FORCE_CHANNELS_REQUEST, opus_int32) \
+    X(kind, type, get_force_channels, OPUS_GET_FORCE_CHANNELS_REQUEST, opus_int32 *) \
+    X(kind, type, set_signal, OPUS_SET_SIGNAL_REQUEST, opus_int32) \
+    X(kind, type, get_signal, OPUS_GET_SIGNAL_REQUEST, opus_int32 *) \
+    X(kind, type, get_lookahead, OPUS_GET_LOOKAHEAD_REQUEST, opus_int32 *) \
+    X(kind, type, set_lsb_depth, OPUS_SET_LSB_DEPTH_REQUEST, opus_int32) \
+    X(kind, type, get_lsb_depth, OPUS_GET_LSB_DEPTH_REQUEST, opus_int32 *) \
+    X(kind, type, set_expert_frame_duration, OPUS_SET_EXPERT_FRAME_DURATION_REQUEST, opus_int32) \
+    X(kind, type, get_expert_frame_duration, OPUS_GET_EXPERT_FRAME_DURATION_REQUEST, opus_int32 *) \
+    X(kind, type, set_prediction_disabled, OPUS_SET_PREDICTION_DISABLED_REQUEST, opus_int32) \
+    X(kind, type, get_prediction_disabled, OPUS_GET_PREDICTION_DISABLED_REQUEST, opus_int32 *)
+
+#define OPUS_RS_DECODER_CTLS(X, kind, type) \
+    X(kind, type, set_gain, OPUS_SET_GAIN_REQUEST, opus_int32) \
+    X(kind, type, get_gain, OPUS_GET_GAIN_REQUEST, opus_int32 *) \
+    X(kind, type, get_pitch, OPUS_GET_PITCH_REQUEST, opus_int32 *) \
+    X(kind, type, get_last_packet_duration, OPUS_GET_LAST_PACKET_DURATION_REQUEST, opus_int32 *)
+
+#define OPUS_RS_DECLARE(kind, type, name, request, arg) \
+    int opus_rs_##kind##_##name(type *st, arg x);
+
+#define OPUS_RS_STATE(X, kind, type, ctls) \
+    OPUS_RS_GENERIC_CTLS(X, kind, type) \
+    ctls(X, kind, type) \
+    int opus_rs_##kind##_reset_state(type *st);
+
+OPUS_RS_STATE(OPUS_RS_DECLARE, encoder, OpusEncoder, OPUS_RS_ENCODER_CTLS)
+OPUS_RS_STATE(OPUS_RS_DECLARE, decoder, OpusDecoder, OPUS_RS_DECODER_CTLS)
+OPUS_RS_STATE(OPUS_RS_DECLARE, multistream_encoder, OpusMSEncoder, OPUS_RS_ENCODER_CTLS)
+OPUS_RS_STATE(OPUS_RS_DECLARE, multistream_decoder, OpusMSDecoder, OPUS_RS_DECODER_CTLS)
+OPUS_RS_STATE(OPUS_RS_DECLARE, projection_encoder, OpusProjectionEncoder, OPUS_RS_ENCODER_CTLS)
+OPUS_RS_STATE(OPUS_RS_DECLARE, projection_decoder, OpusProjectionDecoder, OPUS_RS_DECODER_CTLS)
+
+int opus_rs_multistream_encoder_get_encoder_state(OpusMSEncoder *st, opus_int32 stream_id, OpusEncoder **state);
+int opus_rs_multistream_decoder_get_decoder_state(OpusMSDecoder *st, opus_int32 stream_id, OpusDecoder **state);
+
+int opus_rs_projection_encoder_get_demixing_matrix_gain(OpusProjectionEncoder *st, opus_int32 *x);
+int opus_rs_projection_encoder_get_demixing_matrix_size(OpusProjectionEncoder *st, opus_int32 *x);
+int opus_rs_projection_encoder_get_demixing_matrix(OpusProjectionEncoder *st, unsigned char *matrix, opus_int32 size);
+
+#ifdef OPUS_RS_CUSTOM_MODES
+#include <opus_custom.h>
+
+/* Requests handled by the CELT states behind Opus Custom */
+#define OPUS_RS_CUSTOM_GENERIC_CTLS(X, kind, type) \
+    X(kind, type, get_final_range, OPUS_GET_FINAL_RANGE_REQUEST, opus_uint32 *) \
+    X(kind, type, set_phase_inversion_disabled, OPUS_SET_PHASE_INVERSION_DISABLED_REQUEST, opus_int32) \
+    X(kind, type, get_phase_inversion_disabled, OPUS_GET_PHASE_INVERSION_DISABLED_REQUEST, opus_int32 *)
+
+#define OPUS_RS_CUSTOM_ENCODER_CTLS(X, kind, type) \
+    X(kind, type, set_bitrate, OPUS_SET_BITRATE_REQUEST, opus_int32) \
+    X(kind, type, set_vbr, OPUS_SET_VBR_REQUEST, opus_int32) \
+    X(kind, type, set_vbr_constraint, OPUS_SET_VBR_CONSTRAINT_REQUEST, opus_int32) \
+    X(kind, type, set_complexity, OPUS_SET_COMPLEXITY_REQUEST, opus_int32) \
+    X(kind, type, set_packet_loss_perc, OPUS_SET_PACKET_LOSS_PERC_REQUEST, opus_int32) \
+    X(kind, type, set_lsb_depth, OPUS_SET_LSB_DEPTH_REQUEST, opus_int32) \
+    X(kind, type, get_lsb_depth, OPUS_GET_LSB_DEPTH_REQUEST, opus_int32 *)
+
+#define OPUS_RS_CUSTOM_DECODER_CTLS(X, kind, type) \
+    X(kind, type, get_lookahead, OPUS_GET_LOOKAHEAD_REQUEST, opus_int32 *) \
+    X(kind, type, get_pitch, OPUS_GET_PITCH_REQUEST, opus_int32 *)
+
+#define OPUS_RS_CUSTOM_STATE(X, kind, type, ctls) \
+    OPUS_RS_CUSTOM_GENERIC_CTLS(X, kind, type) \
+    ctls(X, kind, type) \
+    int opus_rs_##kind##_reset_state(type *st);
+
+OPUS_RS_CUSTOM_STATE(OPUS_RS_DECLARE, custom_encoder, OpusCustomEncoder, OPUS_RS_CUSTOM_ENCODER_CTLS)
+OPUS_RS_CUSTOM_STATE(OPUS_RS_DECLARE, custom_decoder, OpusCustomDecoder, OPUS_RS_CUSTOM_DECODER_CTLS)
+#endif
+
+#ifdef OPUS_RS_1_5
+/* libopus 1.5: DRED, and deep PLC/OSCE selected through the decoder complexity */
+int opus_rs_encoder_set_dred_duration(OpusEncoder *st, opus_int32 x);
+int opus_rs_encoder_get_dred_duration(OpusEncoder *st, opus_int32 *x);
+int opus_rs_encoder_set_dnn_blob(OpusEncoder *st, const void *data, opus_int32 len);
+
+int opus_rs_decoder_set_complexity(OpusDecoder *st, opus_int32 x);
+int opus_rs_decoder_get_complexity(OpusDecoder *st, opus_int32 *x);
+int opus_rs_decoder_set_dnn_blob(OpusDecoder *st, const void *data, opus_int32 len);
+
+int opus_rs_dred_decoder_set_dnn_blob(OpusDREDDecoder *st, const void *data, opus_int32 len);
+#endif
+
+#endif
@@ -1,2 +1,4 @@
 #include <opus.h>
 #include <opus_multistream.h>
+#include <opus_projection.h>
+#include "ctl.h"
@@ -65,13 +65,13 @@ fn main() {
     let enc = enc_opt.get_encoder().unwrap();
 
     unsafe {
-        opus_encoder_ctl(enc, OPUS_SET_APPLICATION_REQUEST as i32, OPUS_APPLICATION_AUDIO as i32);
-        opus_encoder_ctl(enc, OPUS_SET_BITRATE_REQUEST as i32, enc_opt.bits_per_second);
-        opus_encoder_ctl(enc, OPUS_SET_BANDWIDTH_REQUEST as i32, OPUS_BANDWIDTH_WIDEBAND);
-        opus_encoder_ctl(enc, OPUS_SET_COMPLEXITY_REQUEST as i32, 10);
-        opus_encoder_ctl(enc, OPUS_SET_VBR_REQUEST as i32, 0);
-        opus_encoder_ctl(enc, OPUS_SET_VBR_CONSTRAINT_REQUEST as i32, 0);
-        opus_encoder_ctl(enc, OPUS_SET_PACKET_LOSS_PERC_REQUEST as i32, 0);
+        opus_rs_encoder_set_application(enc, OPUS_APPLICATION_AUDIO as i32);
+        opus_rs_encoder_set_bitrate(enc, enc_opt.bits_per_second);
+        opus_rs_encoder_set_bandwidth(enc, OPUS_BANDWIDTH_WIDEBAND as i32);
+        opus_rs_encoder_set_complexity(enc, 10);
+        opus_rs_encoder_set_vbr(enc, 0);
+        opus_rs_encoder_set_vbr_constraint(enc, 0);
+        opus_rs_encoder_set_packet_loss_perc(enc, 0);
     }
 
     let mut in_f = File::open(enc_opt.input).unwrap();
@@ -110,9 +110,9 @@ fn main() {
             out_f.write_all(&b).unwrap();
 
             // Write the encoder ec final state
-            let mut val = 0i32;
-            unsafe { opus_encoder_ctl(enc, OPUS_GET_FINAL_RANGE_REQUEST as i32, &mut val) };
-            put_i32b(&mut b, val);
+            let mut val = 0u32;
+            unsafe { opus_rs_encoder_get_final_range(enc, &mut val) };
+            put_i32b(&mut b, val as i32);
             out_f.write_all(&b).unwrap();
 
             // Write the actual packet
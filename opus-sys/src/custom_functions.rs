@@ -0,0 +1,204 @@
+/* opus_custom.h and the Opus Custom wrappers in data/ctl.h, bindgen 0.65.1 layout */
+
+extern "C" {
+    pub fn opus_custom_mode_create(
+        Fs: opus_int32,
+        frame_size: ::std::os::raw::c_int,
+        error: *mut ::std::os::raw::c_int,
+    ) -> *mut OpusCustomMode;
+}
+extern "C" {
+    pub fn opus_custom_mode_destroy(mode: *mut OpusCustomMode);
+}
+extern "C" {
+    pub fn opus_custom_encoder_get_size(
+        mode: *const OpusCustomMode,
+        channels: ::std::os::raw::c_int,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_custom_encoder_init(
+        st: *mut OpusCustomEncoder,
+        mode: *const OpusCustomMode,
+        channels: ::std::os::raw::c_int,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_custom_encoder_create(
+        mode: *const OpusCustomMode,
+        channels: ::std::os::raw::c_int,
+        error: *mut ::std::os::raw::c_int,
+    ) -> *mut OpusCustomEncoder;
+}
+extern "C" {
+    pub fn opus_custom_encoder_destroy(st: *mut OpusCustomEncoder);
+}
+extern "C" {
+    pub fn opus_custom_encode_float(
+        st: *mut OpusCustomEncoder,
+        pcm: *const f32,
+        frame_size: ::std::os::raw::c_int,
+        compressed: *mut ::std::os::raw::c_uchar,
+        maxCompressedBytes: ::std::os::raw::c_int,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_custom_encode(
+        st: *mut OpusCustomEncoder,
+        pcm: *const opus_int16,
+        frame_size: ::std::os::raw::c_int,
+        compressed: *mut ::std::os::raw::c_uchar,
+        maxCompressedBytes: ::std::os::raw::c_int,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_custom_encoder_ctl(
+        st: *mut OpusCustomEncoder,
+        request: ::std::os::raw::c_int,
+        ...
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_custom_decoder_get_size(
+        mode: *const OpusCustomMode,
+        channels: ::std::os::raw::c_int,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_custom_decoder_init(
+        st: *mut OpusCustomDecoder,
+        mode: *const OpusCustomMode,
+        channels: ::std::os::raw::c_int,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_custom_decoder_create(
+        mode: *const OpusCustomMode,
+        channels: ::std::os::raw::c_int,
+        error: *mut ::std::os::raw::c_int,
+    ) -> *mut OpusCustomDecoder;
+}
+extern "C" {
+    pub fn opus_custom_decoder_destroy(st: *mut OpusCustomDecoder);
+}
+extern "C" {
+    pub fn opus_custom_decode_float(
+        st: *mut OpusCustomDecoder,
+        data: *const ::std::os::raw::c_uchar,
+        len: ::std::os::raw::c_int,
+        pcm: *mut f32,
+        frame_size: ::std::os::raw::c_int,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_custom_decode(
+        st: *mut OpusCustomDecoder,
+        data: *const ::std::os::raw::c_uchar,
+        len: ::std::os::raw::c_int,
+        pcm: *mut opus_int16,
+        frame_size: ::std::os::raw::c_int,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_custom_decoder_ctl(
+        st: *mut OpusCustomDecoder,
+        request: ::std::os::raw::c_int,
+        ...
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_custom_encoder_get_final_range(
+        st: *mut OpusCustomEncoder,
+        x: *mut opus_uint32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_custom_encoder_set_phase_inversion_disabled(
+        st: *mut OpusCustomEncoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_custom_encoder_get_phase_inversion_disabled(
+        st: *mut OpusCustomEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_custom_encoder_set_bitrate(
+        st: *mut OpusCustomEncoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_custom_encoder_set_vbr(
+        st: *mut OpusCustomEncoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_custom_encoder_set_vbr_constraint(
+        st: *mut OpusCustomEncoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_custom_encoder_set_complexity(
+        st: *mut OpusCustomEncoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_custom_encoder_set_packet_loss_perc(
+        st: *mut OpusCustomEncoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_custom_encoder_set_lsb_depth(
+        st: *mut OpusCustomEncoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_custom_encoder_get_lsb_depth(
+        st: *mut OpusCustomEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_custom_encoder_reset_state(st: *mut OpusCustomEncoder) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_custom_decoder_get_final_range(
+        st: *mut OpusCustomDecoder,
+        x: *mut opus_uint32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_custom_decoder_set_phase_inversion_disabled(
+        st: *mut OpusCustomDecoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_custom_decoder_get_phase_inversion_disabled(
+        st: *mut OpusCustomDecoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_custom_decoder_get_lookahead(
+        st: *mut OpusCustomDecoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_custom_decoder_get_pitch(
+        st: *mut OpusCustomDecoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_custom_decoder_reset_state(st: *mut OpusCustomDecoder) -> ::std::os::raw::c_int;
+}
@@ -0,0 +1,15 @@
+/* libopus 1.5 additions to opus_defines.h and opus.h, bindgen 0.65.1 layout */
+
+pub const OPUS_SET_DRED_DURATION_REQUEST: u32 = 4050;
+pub const OPUS_GET_DRED_DURATION_REQUEST: u32 = 4051;
+pub const OPUS_SET_DNN_BLOB_REQUEST: u32 = 4052;
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct OpusDREDDecoder {
+    _unused: [u8; 0],
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct OpusDRED {
+    _unused: [u8; 0],
+}
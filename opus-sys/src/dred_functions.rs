@@ -0,0 +1,112 @@
+/* libopus 1.5 additions to opus.h and data/ctl.h, bindgen 0.65.1 layout */
+
+extern "C" {
+    pub fn opus_dred_decoder_get_size() -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_dred_decoder_create(error: *mut ::std::os::raw::c_int) -> *mut OpusDREDDecoder;
+}
+extern "C" {
+    pub fn opus_dred_decoder_init(dec: *mut OpusDREDDecoder) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_dred_decoder_destroy(dec: *mut OpusDREDDecoder);
+}
+extern "C" {
+    pub fn opus_dred_decoder_ctl(
+        dred_dec: *mut OpusDREDDecoder,
+        request: ::std::os::raw::c_int,
+        ...
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_dred_get_size() -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_dred_alloc(error: *mut ::std::os::raw::c_int) -> *mut OpusDRED;
+}
+extern "C" {
+    pub fn opus_dred_free(dec: *mut OpusDRED);
+}
+extern "C" {
+    pub fn opus_dred_parse(
+        dred_dec: *mut OpusDREDDecoder,
+        dred: *mut OpusDRED,
+        data: *const ::std::os::raw::c_uchar,
+        len: opus_int32,
+        max_dred_samples: opus_int32,
+        sampling_rate: opus_int32,
+        dred_end: *mut ::std::os::raw::c_int,
+        defer_processing: ::std::os::raw::c_int,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_dred_process(
+        dred_dec: *mut OpusDREDDecoder,
+        src: *const OpusDRED,
+        dst: *mut OpusDRED,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_decoder_dred_decode(
+        st: *mut OpusDecoder,
+        dred: *const OpusDRED,
+        dred_offset: opus_int32,
+        pcm: *mut opus_int16,
+        frame_size: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_decoder_dred_decode_float(
+        st: *mut OpusDecoder,
+        dred: *const OpusDRED,
+        dred_offset: opus_int32,
+        pcm: *mut f32,
+        frame_size: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_encoder_set_dred_duration(
+        st: *mut OpusEncoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_encoder_get_dred_duration(
+        st: *mut OpusEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_encoder_set_dnn_blob(
+        st: *mut OpusEncoder,
+        data: *const ::std::os::raw::c_void,
+        len: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_decoder_set_complexity(
+        st: *mut OpusDecoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_decoder_get_complexity(
+        st: *mut OpusDecoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_decoder_set_dnn_blob(
+        st: *mut OpusDecoder,
+        data: *const ::std::os::raw::c_void,
+        len: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_dred_decoder_set_dnn_blob(
+        st: *mut OpusDREDDecoder,
+        data: *const ::std::os::raw::c_void,
+        len: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
@@ -0,0 +1,557 @@
+//! Runtime loading of libopus.
+//!
+//! With the `dlopen` feature nothing is linked at build time. The library is
+//! opened the first time a function is called, from the path given to
+//! [`load`], from the `OPUS_LIBRARY_PATH` environment variable or from the
+//! platform default names, in that order.
+//!
+//! The variadic `opus_*_ctl` entry points cannot be re-exported as Rust
+//! functions, use the typed `opus_rs_*` wrappers instead.
+//!
+//! The libopus 1.5 entry points are resolved under the `opus_1_5` cfg flag,
+//! which this build sets only along with the `dred` feature.
+
+use super::*;
+
+use std::env;
+use std::error;
+use std::ffi::{CStr, OsStr};
+use std::fmt;
+#[cfg(opus_1_5)]
+use std::os::raw::c_void;
+use std::os::raw::{c_char, c_int, c_uchar};
+use std::sync::{Mutex, OnceLock};
+
+/// Environment variable used to locate the library when [`load`] is not called.
+pub const LIBRARY_PATH_ENV: &str = "OPUS_LIBRARY_PATH";
+
+#[cfg(target_os = "windows")]
+const DEFAULT_NAMES: &[&str] = &["opus.dll", "libopus-0.dll"];
+#[cfg(target_os = "macos")]
+const DEFAULT_NAMES: &[&str] = &["libopus.0.dylib", "libopus.dylib"];
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+const DEFAULT_NAMES: &[&str] = &["libopus.so.0", "libopus.so"];
+
+static LIBRARY: OnceLock<Library> = OnceLock::new();
+static LOADING: Mutex<()> = Mutex::new(());
+
+/// The library could not be opened or lacks a required symbol.
+#[derive(Clone, Debug)]
+pub struct LoadError(String);
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "cannot load libopus: {}", self.0)
+    }
+}
+
+impl error::Error for LoadError {}
+
+macro_rules! functions {
+    (
+        variadic {
+            $($(#[$vmeta:meta])* $vname:ident => $vptr:ident($($vty:ty),*) -> $vret:ty;)*
+        }
+
+        $($(#[$meta:meta])* $name:ident($($arg:ident: $ty:ty),*) $(-> $ret:ty)?;)*
+    ) => {
+        struct Functions {
+            $($(#[$meta])* $name: unsafe extern "C" fn($($ty),*) $(-> $ret)?,)*
+            $($(#[$vmeta])* $vname: unsafe extern "C" fn($($vty),*, ...) -> $vret,)*
+        }
+
+        // Called by the typed wrappers in data/ctl.c
+        extern "C" {
+            $($(#[$vmeta])* static mut $vptr: Option<unsafe extern "C" fn($($vty),*, ...) -> $vret>;)*
+        }
+
+        impl Functions {
+            unsafe fn load(lib: &libloading::Library) -> Result<Functions, libloading::Error> {
+                Ok(Functions {
+                    $($(#[$meta])* $name: *lib.get(concat!(stringify!($name), "\0").as_bytes())?,)*
+                    $($(#[$vmeta])* $vname: *lib.get(concat!(stringify!($vname), "\0").as_bytes())?,)*
+                })
+            }
+
+            /// Hands the variadic ctl entry points over to the C wrappers.
+            unsafe fn install(&self) {
+                $($(#[$vmeta])* { $vptr = Some(self.$vname); })*
+            }
+        }
+
+        $(
+            /// # Panics
+            ///
+            /// Panics if libopus cannot be loaded.
+            ///
+            /// # Safety
+            ///
+            /// Same contract as the libopus function of the same name.
+            $(#[$meta])*
+            #[allow(clippy::too_many_arguments)]
+            pub unsafe fn $name($($arg: $ty),*) $(-> $ret)? {
+                (functions().$name)($($arg),*)
+            }
+        )*
+    };
+}
+
+macro_rules! ctls {
+    ($($state:ty {
+        $($(#[$meta:meta])* $name:ident($($arg:ident: $ty:ty),*);)*
+    })*) => {
+        mod shim {
+            use super::*;
+
+            extern "C" {
+                $($($(#[$meta])* pub fn $name(st: *mut $state $(, $arg: $ty)*) -> c_int;)*)*
+            }
+        }
+
+        $($(
+            /// # Panics
+            ///
+            /// Panics if libopus cannot be loaded.
+            ///
+            /// # Safety
+            ///
+            /// Same contract as the libopus function of the same name.
+            $(#[$meta])*
+            pub unsafe fn $name(st: *mut $state $(, $arg: $ty)*) -> c_int {
+                // The wrapper calls through the entry point stored on load
+                functions();
+                shim::$name(st $(, $arg)*)
+            }
+        )*)*
+    };
+}
+
+/// A loaded libopus.
+pub struct Library {
+    fns: Functions,
+    version: String,
+    _lib: libloading::Library,
+}
+
+impl Library {
+    /// Opens the library at `path` and resolves every symbol the bindings use.
+    ///
+    /// # Safety
+    ///
+    /// Loading a library runs its initialization code, `path` must point to
+    /// a libopus build.
+    pub unsafe fn open<P: AsRef<OsStr>>(path: P) -> Result<Library, LoadError> {
+        let path = path.as_ref();
+        let lib = libloading::Library::new(path)
+            .map_err(|e| LoadError(format!("{}: {}", path.to_string_lossy(), e)))?;
+        let fns = Functions::load(&lib)
+            .map_err(|e| LoadError(format!("{}: {}", path.to_string_lossy(), e)))?;
+        let version = CStr::from_ptr((fns.opus_get_version_string)())
+            .to_string_lossy()
+            .into_owned();
+
+        Ok(Library {
+            fns,
+            version,
+            _lib: lib,
+        })
+    }
+
+    /// The string reported by `opus_get_version_string`.
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+}
+
+/// Loads libopus from `path` and makes it the library every function uses.
+///
+/// Once a library is loaded it stays loaded for the lifetime of the process,
+/// later calls return it regardless of `path`.
+///
+/// # Safety
+///
+/// Loading a library runs its initialization code, `path` must point to
+/// a libopus build.
+pub unsafe fn load<P: AsRef<OsStr>>(path: P) -> Result<&'static Library, LoadError> {
+    let _guard = LOADING.lock().unwrap_or_else(|e| e.into_inner());
+
+    if let Some(lib) = LIBRARY.get() {
+        return Ok(lib);
+    }
+
+    let lib = Library::open(path)?;
+    lib.fns.install();
+
+    Ok(LIBRARY.get_or_init(|| lib))
+}
+
+/// Returns the loaded libopus, loading it from the default location if needed.
+pub fn library() -> Result<&'static Library, LoadError> {
+    if let Some(lib) = LIBRARY.get() {
+        return Ok(lib);
+    }
+
+    // Like a linked libopus, whatever the environment or the platform
+    // default names point to is trusted to be one
+    if let Some(path) = env::var_os(LIBRARY_PATH_ENV) {
+        return unsafe { load(path) };
+    }
+
+    let mut err = LoadError("no library name to try".to_owned());
+    for name in DEFAULT_NAMES {
+        match unsafe { load(name) } {
+            Ok(lib) => return Ok(lib),
+            Err(e) => err = e,
+        }
+    }
+
+    Err(err)
+}
+
+fn functions() -> &'static Functions {
+    match library() {
+        Ok(lib) => &lib.fns,
+        Err(e) => panic!("{}", e),
+    }
+}
+
+functions! {
+    variadic {
+        opus_encoder_ctl => opus_rs_dl_encoder_ctl(*mut OpusEncoder, c_int) -> c_int;
+        opus_decoder_ctl => opus_rs_dl_decoder_ctl(*mut OpusDecoder, c_int) -> c_int;
+        opus_multistream_encoder_ctl => opus_rs_dl_multistream_encoder_ctl(*mut OpusMSEncoder, c_int) -> c_int;
+        opus_multistream_decoder_ctl => opus_rs_dl_multistream_decoder_ctl(*mut OpusMSDecoder, c_int) -> c_int;
+        opus_projection_encoder_ctl => opus_rs_dl_projection_encoder_ctl(*mut OpusProjectionEncoder, c_int) -> c_int;
+        opus_projection_decoder_ctl => opus_rs_dl_projection_decoder_ctl(*mut OpusProjectionDecoder, c_int) -> c_int;
+        #[cfg(opus_1_5)]
+        opus_dred_decoder_ctl => opus_rs_dl_dred_decoder_ctl(*mut OpusDREDDecoder, c_int) -> c_int;
+        #[cfg(feature = "custom-modes")]
+        opus_custom_encoder_ctl => opus_rs_dl_custom_encoder_ctl(*mut OpusCustomEncoder, c_int) -> c_int;
+        #[cfg(feature = "custom-modes")]
+        opus_custom_decoder_ctl => opus_rs_dl_custom_decoder_ctl(*mut OpusCustomDecoder, c_int) -> c_int;
+    }
+
+    opus_strerror(error: c_int) -> *const c_char;
+    opus_get_version_string() -> *const c_char;
+    opus_encoder_get_size(channels: c_int) -> c_int;
+    opus_encoder_create(Fs: opus_int32, channels: c_int, application: c_int, error: *mut c_int) -> *mut OpusEncoder;
+    opus_encoder_init(st: *mut OpusEncoder, Fs: opus_int32, channels: c_int, application: c_int) -> c_int;
+    opus_encode(st: *mut OpusEncoder, pcm: *const opus_int16, frame_size: c_int, data: *mut c_uchar, max_data_bytes: opus_int32) -> opus_int32;
+    opus_encode_float(st: *mut OpusEncoder, pcm: *const f32, frame_size: c_int, data: *mut c_uchar, max_data_bytes: opus_int32) -> opus_int32;
+    opus_encoder_destroy(st: *mut OpusEncoder);
+    opus_decoder_get_size(channels: c_int) -> c_int;
+    opus_decoder_create(Fs: opus_int32, channels: c_int, error: *mut c_int) -> *mut OpusDecoder;
+    opus_decoder_init(st: *mut OpusDecoder, Fs: opus_int32, channels: c_int) -> c_int;
+    opus_decode(st: *mut OpusDecoder, data: *const c_uchar, len: opus_int32, pcm: *mut opus_int16, frame_size: c_int, decode_fec: c_int) -> c_int;
+    opus_decode_float(st: *mut OpusDecoder, data: *const c_uchar, len: opus_int32, pcm: *mut f32, frame_size: c_int, decode_fec: c_int) -> c_int;
+    opus_decoder_destroy(st: *mut OpusDecoder);
+    opus_packet_parse(data: *const c_uchar, len: opus_int32, out_toc: *mut c_uchar, frames: *mut *const c_uchar, size: *mut opus_int16, payload_offset: *mut c_int) -> c_int;
+    opus_packet_get_bandwidth(data: *const c_uchar) -> c_int;
+    opus_packet_get_samples_per_frame(data: *const c_uchar, Fs: opus_int32) -> c_int;
+    opus_packet_get_nb_channels(data: *const c_uchar) -> c_int;
+    opus_packet_get_nb_frames(packet: *const c_uchar, len: opus_int32) -> c_int;
+    opus_packet_get_nb_samples(packet: *const c_uchar, len: opus_int32, Fs: opus_int32) -> c_int;
+    opus_decoder_get_nb_samples(dec: *const OpusDecoder, packet: *const c_uchar, len: opus_int32) -> c_int;
+    opus_pcm_soft_clip(pcm: *mut f32, frame_size: c_int, channels: c_int, softclip_mem: *mut f32);
+    opus_repacketizer_get_size() -> c_int;
+    opus_repacketizer_init(rp: *mut OpusRepacketizer) -> *mut OpusRepacketizer;
+    opus_repacketizer_create() -> *mut OpusRepacketizer;
+    opus_repacketizer_destroy(rp: *mut OpusRepacketizer);
+    opus_repacketizer_cat(rp: *mut OpusRepacketizer, data: *const c_uchar, len: opus_int32) -> c_int;
+    opus_repacketizer_out_range(rp: *mut OpusRepacketizer, begin: c_int, end: c_int, data: *mut c_uchar, maxlen: opus_int32) -> opus_int32;
+    opus_repacketizer_get_nb_frames(rp: *mut OpusRepacketizer) -> c_int;
+    opus_repacketizer_out(rp: *mut OpusRepacketizer, data: *mut c_uchar, maxlen: opus_int32) -> opus_int32;
+    opus_packet_pad(data: *mut c_uchar, len: opus_int32, new_len: opus_int32) -> c_int;
+    opus_packet_unpad(data: *mut c_uchar, len: opus_int32) -> opus_int32;
+    opus_multistream_packet_pad(data: *mut c_uchar, len: opus_int32, new_len: opus_int32, nb_streams: c_int) -> c_int;
+    opus_multistream_packet_unpad(data: *mut c_uchar, len: opus_int32, nb_streams: c_int) -> opus_int32;
+    opus_multistream_encoder_get_size(streams: c_int, coupled_streams: c_int) -> opus_int32;
+    opus_multistream_surround_encoder_get_size(channels: c_int, mapping_family: c_int) -> opus_int32;
+    opus_multistream_encoder_create(Fs: opus_int32, channels: c_int, streams: c_int, coupled_streams: c_int, mapping: *const c_uchar, application: c_int, error: *mut c_int) -> *mut OpusMSEncoder;
+    opus_multistream_surround_encoder_create(Fs: opus_int32, channels: c_int, mapping_family: c_int, streams: *mut c_int, coupled_streams: *mut c_int, mapping: *mut c_uchar, application: c_int, error: *mut c_int) -> *mut OpusMSEncoder;
+    opus_multistream_encoder_init(st: *mut OpusMSEncoder, Fs: opus_int32, channels: c_int, streams: c_int, coupled_streams: c_int, mapping: *const c_uchar, application: c_int) -> c_int;
+    opus_multistream_surround_encoder_init(st: *mut OpusMSEncoder, Fs: opus_int32, channels: c_int, mapping_family: c_int, streams: *mut c_int, coupled_streams: *mut c_int, mapping: *mut c_uchar, application: c_int) -> c_int;
+    opus_multistream_encode(st: *mut OpusMSEncoder, pcm: *const opus_int16, frame_size: c_int, data: *mut c_uchar, max_data_bytes: opus_int32) -> c_int;
+    opus_multistream_encode_float(st: *mut OpusMSEncoder, pcm: *const f32, frame_size: c_int, data: *mut c_uchar, max_data_bytes: opus_int32) -> c_int;
+    opus_multistream_encoder_destroy(st: *mut OpusMSEncoder);
+    opus_multistream_decoder_get_size(streams: c_int, coupled_streams: c_int) -> opus_int32;
+    opus_multistream_decoder_create(Fs: opus_int32, channels: c_int, streams: c_int, coupled_streams: c_int, mapping: *const c_uchar, error: *mut c_int) -> *mut OpusMSDecoder;
+    opus_multistream_decoder_init(st: *mut OpusMSDecoder, Fs: opus_int32, channels: c_int, streams: c_int, coupled_streams: c_int, mapping: *const c_uchar) -> c_int;
+    opus_multistream_decode(st: *mut OpusMSDecoder, data: *const c_uchar, len: opus_int32, pcm: *mut opus_int16, frame_size: c_int, decode_fec: c_int) -> c_int;
+    opus_multistream_decode_float(st: *mut OpusMSDecoder, data: *const c_uchar, len: opus_int32, pcm: *mut f32, frame_size: c_int, decode_fec: c_int) -> c_int;
+    opus_multistream_decoder_destroy(st: *mut OpusMSDecoder);
+    opus_projection_ambisonics_encoder_get_size(channels: c_int, mapping_family: c_int) -> opus_int32;
+    opus_projection_ambisonics_encoder_create(Fs: opus_int32, channels: c_int, mapping_family: c_int, streams: *mut c_int, coupled_streams: *mut c_int, application: c_int, error: *mut c_int) -> *mut OpusProjectionEncoder;
+    opus_projection_ambisonics_encoder_init(st: *mut OpusProjectionEncoder, Fs: opus_int32, channels: c_int, mapping_family: c_int, streams: *mut c_int, coupled_streams: *mut c_int, application: c_int) -> c_int;
+    opus_projection_encode(st: *mut OpusProjectionEncoder, pcm: *const opus_int16, frame_size: c_int, data: *mut c_uchar, max_data_bytes: opus_int32) -> c_int;
+    opus_projection_encode_float(st: *mut OpusProjectionEncoder, pcm: *const f32, frame_size: c_int, data: *mut c_uchar, max_data_bytes: opus_int32) -> c_int;
+    opus_projection_encoder_destroy(st: *mut OpusProjectionEncoder);
+    opus_projection_decoder_get_size(channels: c_int, streams: c_int, coupled_streams: c_int) -> opus_int32;
+    opus_projection_decoder_create(Fs: opus_int32, channels: c_int, streams: c_int, coupled_streams: c_int, demixing_matrix: *mut c_uchar, demixing_matrix_size: opus_int32, error: *mut c_int) -> *mut OpusProjectionDecoder;
+    opus_projection_decoder_init(st: *mut OpusProjectionDecoder, Fs: opus_int32, channels: c_int, streams: c_int, coupled_streams: c_int, demixing_matrix: *mut c_uchar, demixing_matrix_size: opus_int32) -> c_int;
+    opus_projection_decode(st: *mut OpusProjectionDecoder, data: *const c_uchar, len: opus_int32, pcm: *mut opus_int16, frame_size: c_int, decode_fec: c_int) -> c_int;
+    opus_projection_decode_float(st: *mut OpusProjectionDecoder, data: *const c_uchar, len: opus_int32, pcm: *mut f32, frame_size: c_int, decode_fec: c_int) -> c_int;
+    opus_projection_decoder_destroy(st: *mut OpusProjectionDecoder);
+    #[cfg(opus_1_5)]
+    opus_dred_decoder_get_size() -> c_int;
+    #[cfg(opus_1_5)]
+    opus_dred_decoder_create(error: *mut c_int) -> *mut OpusDREDDecoder;
+    #[cfg(opus_1_5)]
+    opus_dred_decoder_init(dec: *mut OpusDREDDecoder) -> c_int;
+    #[cfg(opus_1_5)]
+    opus_dred_decoder_destroy(dec: *mut OpusDREDDecoder);
+    #[cfg(opus_1_5)]
+    opus_dred_get_size() -> c_int;
+    #[cfg(opus_1_5)]
+    opus_dred_alloc(error: *mut c_int) -> *mut OpusDRED;
+    #[cfg(opus_1_5)]
+    opus_dred_free(dec: *mut OpusDRED);
+    #[cfg(opus_1_5)]
+    opus_dred_parse(dred_dec: *mut OpusDREDDecoder, dred: *mut OpusDRED, data: *const c_uchar, len: opus_int32, max_dred_samples: opus_int32, sampling_rate: opus_int32, dred_end: *mut c_int, defer_processing: c_int) -> c_int;
+    #[cfg(opus_1_5)]
+    opus_dred_process(dred_dec: *mut OpusDREDDecoder, src: *const OpusDRED, dst: *mut OpusDRED) -> c_int;
+    #[cfg(opus_1_5)]
+    opus_decoder_dred_decode(st: *mut OpusDecoder, dred: *const OpusDRED, dred_offset: opus_int32, pcm: *mut opus_int16, frame_size: opus_int32) -> c_int;
+    #[cfg(opus_1_5)]
+    opus_decoder_dred_decode_float(st: *mut OpusDecoder, dred: *const OpusDRED, dred_offset: opus_int32, pcm: *mut f32, frame_size: opus_int32) -> c_int;
+    #[cfg(feature = "custom-modes")]
+    opus_custom_mode_create(Fs: opus_int32, frame_size: c_int, error: *mut c_int) -> *mut OpusCustomMode;
+    #[cfg(feature = "custom-modes")]
+    opus_custom_mode_destroy(mode: *mut OpusCustomMode);
+    #[cfg(feature = "custom-modes")]
+    opus_custom_encoder_get_size(mode: *const OpusCustomMode, channels: c_int) -> c_int;
+    #[cfg(feature = "custom-modes")]
+    opus_custom_encoder_init(st: *mut OpusCustomEncoder, mode: *const OpusCustomMode, channels: c_int) -> c_int;
+    #[cfg(feature = "custom-modes")]
+    opus_custom_encoder_create(mode: *const OpusCustomMode, channels: c_int, error: *mut c_int) -> *mut OpusCustomEncoder;
+    #[cfg(feature = "custom-modes")]
+    opus_custom_encoder_destroy(st: *mut OpusCustomEncoder);
+    #[cfg(feature = "custom-modes")]
+    opus_custom_encode_float(st: *mut OpusCustomEncoder, pcm: *const f32, frame_size: c_int, compressed: *mut c_uchar, maxCompressedBytes: c_int) -> c_int;
+    #[cfg(feature = "custom-modes")]
+    opus_custom_encode(st: *mut OpusCustomEncoder, pcm: *const opus_int16, frame_size: c_int, compressed: *mut c_uchar, maxCompressedBytes: c_int) -> c_int;
+    #[cfg(feature = "custom-modes")]
+    opus_custom_decoder_get_size(mode: *const OpusCustomMode, channels: c_int) -> c_int;
+    #[cfg(feature = "custom-modes")]
+    opus_custom_decoder_init(st: *mut OpusCustomDecoder, mode: *const OpusCustomMode, channels: c_int) -> c_int;
+    #[cfg(feature = "custom-modes")]
+    opus_custom_decoder_create(mode: *const OpusCustomMode, channels: c_int, error: *mut c_int) -> *mut OpusCustomDecoder;
+    #[cfg(feature = "custom-modes")]
+    opus_custom_decoder_destroy(st: *mut OpusCustomDecoder);
+    #[cfg(feature = "custom-modes")]
+    opus_custom_decode_float(st: *mut OpusCustomDecoder, data: *const c_uchar, len: c_int, pcm: *mut f32, frame_size: c_int) -> c_int;
+    #[cfg(feature = "custom-modes")]
+    opus_custom_decode(st: *mut OpusCustomDecoder, data: *const c_uchar, len: c_int, pcm: *mut opus_int16, frame_size: c_int) -> c_int;
+}
+
+ctls! {
+    OpusEncoder {
+        opus_rs_encoder_get_bandwidth(x: *mut opus_int32);
+        opus_rs_encoder_get_sample_rate(x: *mut opus_int32);
+        opus_rs_encoder_get_final_range(x: *mut opus_uint32);
+        opus_rs_encoder_set_phase_inversion_disabled(x: opus_int32);
+        opus_rs_encoder_get_phase_inversion_disabled(x: *mut opus_int32);
+        opus_rs_encoder_set_application(x: opus_int32);
+        opus_rs_encoder_get_application(x: *mut opus_int32);
+        opus_rs_encoder_set_bitrate(x: opus_int32);
+        opus_rs_encoder_get_bitrate(x: *mut opus_int32);
+        opus_rs_encoder_set_max_bandwidth(x: opus_int32);
+        opus_rs_encoder_get_max_bandwidth(x: *mut opus_int32);
+        opus_rs_encoder_set_vbr(x: opus_int32);
+        opus_rs_encoder_get_vbr(x: *mut opus_int32);
+        opus_rs_encoder_set_bandwidth(x: opus_int32);
+        opus_rs_encoder_set_complexity(x: opus_int32);
+        opus_rs_encoder_get_complexity(x: *mut opus_int32);
+        opus_rs_encoder_set_inband_fec(x: opus_int32);
+        opus_rs_encoder_get_inband_fec(x: *mut opus_int32);
+        opus_rs_encoder_set_packet_loss_perc(x: opus_int32);
+        opus_rs_encoder_get_packet_loss_perc(x: *mut opus_int32);
+        opus_rs_encoder_set_dtx(x: opus_int32);
+        opus_rs_encoder_get_dtx(x: *mut opus_int32);
+        opus_rs_encoder_set_vbr_constraint(x: opus_int32);
+        opus_rs_encoder_get_vbr_constraint(x: *mut opus_int32);
+        opus_rs_encoder_set_force_channels(x: opus_int32);
+        opus_rs_encoder_get_force_channels(x: *mut opus_int32);
+        opus_rs_encoder_set_signal(x: opus_int32);
+        opus_rs_encoder_get_signal(x: *mut opus_int32);
+        opus_rs_encoder_get_lookahead(x: *mut opus_int32);
+        opus_rs_encoder_set_lsb_depth(x: opus_int32);
+        opus_rs_encoder_get_lsb_depth(x: *mut opus_int32);
+        opus_rs_encoder_set_expert_frame_duration(x: opus_int32);
+        opus_rs_encoder_get_expert_frame_duration(x: *mut opus_int32);
+        opus_rs_encoder_set_prediction_disabled(x: opus_int32);
+        opus_rs_encoder_get_prediction_disabled(x: *mut opus_int32);
+        opus_rs_encoder_reset_state();
+        #[cfg(opus_1_5)]
+        opus_rs_encoder_set_dred_duration(x: opus_int32);
+        #[cfg(opus_1_5)]
+        opus_rs_encoder_get_dred_duration(x: *mut opus_int32);
+        #[cfg(opus_1_5)]
+        opus_rs_encoder_set_dnn_blob(data: *const c_void, len: opus_int32);
+    }
+    OpusDecoder {
+        opus_rs_decoder_get_bandwidth(x: *mut opus_int32);
+        opus_rs_decoder_get_sample_rate(x: *mut opus_int32);
+        opus_rs_decoder_get_final_range(x: *mut opus_uint32);
+        opus_rs_decoder_set_phase_inversion_disabled(x: opus_int32);
+        opus_rs_decoder_get_phase_inversion_disabled(x: *mut opus_int32);
+        opus_rs_decoder_set_gain(x: opus_int32);
+        opus_rs_decoder_get_gain(x: *mut opus_int32);
+        opus_rs_decoder_get_pitch(x: *mut opus_int32);
+        opus_rs_decoder_get_last_packet_duration(x: *mut opus_int32);
+        opus_rs_decoder_reset_state();
+        #[cfg(opus_1_5)]
+        opus_rs_decoder_set_complexity(x: opus_int32);
+        #[cfg(opus_1_5)]
+        opus_rs_decoder_get_complexity(x: *mut opus_int32);
+        #[cfg(opus_1_5)]
+        opus_rs_decoder_set_dnn_blob(data: *const c_void, len: opus_int32);
+    }
+    OpusMSEncoder {
+        opus_rs_multistream_encoder_get_bandwidth(x: *mut opus_int32);
+        opus_rs_multistream_encoder_get_sample_rate(x: *mut opus_int32);
+        opus_rs_multistream_encoder_get_final_range(x: *mut opus_uint32);
+        opus_rs_multistream_encoder_set_phase_inversion_disabled(x: opus_int32);
+        opus_rs_multistream_encoder_get_phase_inversion_disabled(x: *mut opus_int32);
+        opus_rs_multistream_encoder_set_application(x: opus_int32);
+        opus_rs_multistream_encoder_get_application(x: *mut opus_int32);
+        opus_rs_multistream_encoder_set_bitrate(x: opus_int32);
+        opus_rs_multistream_encoder_get_bitrate(x: *mut opus_int32);
+        opus_rs_multistream_encoder_set_max_bandwidth(x: opus_int32);
+        opus_rs_multistream_encoder_get_max_bandwidth(x: *mut opus_int32);
+        opus_rs_multistream_encoder_set_vbr(x: opus_int32);
+        opus_rs_multistream_encoder_get_vbr(x: *mut opus_int32);
+        opus_rs_multistream_encoder_set_bandwidth(x: opus_int32);
+        opus_rs_multistream_encoder_set_complexity(x: opus_int32);
+        opus_rs_multistream_encoder_get_complexity(x: *mut opus_int32);
+        opus_rs_multistream_encoder_set_inband_fec(x: opus_int32);
+        opus_rs_multistream_encoder_get_inband_fec(x: *mut opus_int32);
+        opus_rs_multistream_encoder_set_packet_loss_perc(x: opus_int32);
+        opus_rs_multistream_encoder_get_packet_loss_perc(x: *mut opus_int32);
+        opus_rs_multistream_encoder_set_dtx(x: opus_int32);
+        opus_rs_multistream_encoder_get_dtx(x: *mut opus_int32);
+        opus_rs_multistream_encoder_set_vbr_constraint(x: opus_int32);
+        opus_rs_multistream_encoder_get_vbr_constraint(x: *mut opus_int32);
+        opus_rs_multistream_encoder_set_force_channels(x: opus_int32);
+        opus_rs_multistream_encoder_get_force_channels(x: *mut opus_int32);
+        opus_rs_multistream_encoder_set_signal(x: opus_int32);
+        opus_rs_multistream_encoder_get_signal(x: *mut opus_int32);
+        opus_rs_multistream_encoder_get_lookahead(x: *mut opus_int32);
+        opus_rs_multistream_encoder_set_lsb_depth(x: opus_int32);
+        opus_rs_multistream_encoder_get_lsb_depth(x: *mut opus_int32);
+        opus_rs_multistream_encoder_set_expert_frame_duration(x: opus_int32);
+        opus_rs_multistream_encoder_get_expert_frame_duration(x: *mut opus_int32);
+        opus_rs_multistream_encoder_set_prediction_disabled(x: opus_int32);
+        opus_rs_multistream_encoder_get_prediction_disabled(x: *mut opus_int32);
+        opus_rs_multistream_encoder_reset_state();
+        opus_rs_multistream_encoder_get_encoder_state(stream_id: opus_int32, state: *mut *mut OpusEncoder);
+    }
+    OpusMSDecoder {
+        opus_rs_multistream_decoder_get_bandwidth(x: *mut opus_int32);
+        opus_rs_multistream_decoder_get_sample_rate(x: *mut opus_int32);
+        opus_rs_multistream_decoder_get_final_range(x: *mut opus_uint32);
+        opus_rs_multistream_decoder_set_phase_inversion_disabled(x: opus_int32);
+        opus_rs_multistream_decoder_get_phase_inversion_disabled(x: *mut opus_int32);
+        opus_rs_multistream_decoder_set_gain(x: opus_int32);
+        opus_rs_multistream_decoder_get_gain(x: *mut opus_int32);
+        opus_rs_multistream_decoder_get_pitch(x: *mut opus_int32);
+        opus_rs_multistream_decoder_get_last_packet_duration(x: *mut opus_int32);
+        opus_rs_multistream_decoder_reset_state();
+        opus_rs_multistream_decoder_get_decoder_state(stream_id: opus_int32, state: *mut *mut OpusDecoder);
+    }
+    OpusProjectionEncoder {
+        opus_rs_projection_encoder_get_bandwidth(x: *mut opus_int32);
+        opus_rs_projection_encoder_get_sample_rate(x: *mut opus_int32);
+        opus_rs_projection_encoder_get_final_range(x: *mut opus_uint32);
+        opus_rs_projection_encoder_set_phase_inversion_disabled(x: opus_int32);
+        opus_rs_projection_encoder_get_phase_inversion_disabled(x: *mut opus_int32);
+        opus_rs_projection_encoder_set_application(x: opus_int32);
+        opus_rs_projection_encoder_get_application(x: *mut opus_int32);
+        opus_rs_projection_encoder_set_bitrate(x: opus_int32);
+        opus_rs_projection_encoder_get_bitrate(x: *mut opus_int32);
+        opus_rs_projection_encoder_set_max_bandwidth(x: opus_int32);
+        opus_rs_projection_encoder_get_max_bandwidth(x: *mut opus_int32);
+        opus_rs_projection_encoder_set_vbr(x: opus_int32);
+        opus_rs_projection_encoder_get_vbr(x: *mut opus_int32);
+        opus_rs_projection_encoder_set_bandwidth(x: opus_int32);
+        opus_rs_projection_encoder_set_complexity(x: opus_int32);
+        opus_rs_projection_encoder_get_complexity(x: *mut opus_int32);
+        opus_rs_projection_encoder_set_inband_fec(x: opus_int32);
+        opus_rs_projection_encoder_get_inband_fec(x: *mut opus_int32);
+        opus_rs_projection_encoder_set_packet_loss_perc(x: opus_int32);
+        opus_rs_projection_encoder_get_packet_loss_perc(x: *mut opus_int32);
+        opus_rs_projection_encoder_set_dtx(x: opus_int32);
+        opus_rs_projection_encoder_get_dtx(x: *mut opus_int32);
+        opus_rs_projection_encoder_set_vbr_constraint(x: opus_int32);
+        opus_rs_projection_encoder_get_vbr_constraint(x: *mut opus_int32);
+        opus_rs_projection_encoder_set_force_channels(x: opus_int32);
+        opus_rs_projection_encoder_get_force_channels(x: *mut opus_int32);
+        opus_rs_projection_encoder_set_signal(x: opus_int32);
+        opus_rs_projection_encoder_get_signal(x: *mut opus_int32);
+        opus_rs_projection_encoder_get_lookahead(x: *mut opus_int32);
+        opus_rs_projection_encoder_set_lsb_depth(x: opus_int32);
+        opus_rs_projection_encoder_get_lsb_depth(x: *mut opus_int32);
+        opus_rs_projection_encoder_set_expert_frame_duration(x: opus_int32);
+        opus_rs_projection_encoder_get_expert_frame_duration(x: *mut opus_int32);
+        opus_rs_projection_encoder_set_prediction_disabled(x: opus_int32);
+        opus_rs_projection_encoder_get_prediction_disabled(x: *mut opus_int32);
+        opus_rs_projection_encoder_reset_state();
+        opus_rs_projection_encoder_get_demixing_matrix_gain(x: *mut opus_int32);
+        opus_rs_projection_encoder_get_demixing_matrix_size(x: *mut opus_int32);
+        opus_rs_projection_encoder_get_demixing_matrix(matrix: *mut c_uchar, size: opus_int32);
+    }
+    OpusProjectionDecoder {
+        opus_rs_projection_decoder_get_bandwidth(x: *mut opus_int32);
+        opus_rs_projection_decoder_get_sample_rate(x: *mut opus_int32);
+        opus_rs_projection_decoder_get_final_range(x: *mut opus_uint32);
+        opus_rs_projection_decoder_set_phase_inversion_disabled(x: opus_int32);
+        opus_rs_projection_decoder_get_phase_inversion_disabled(x: *mut opus_int32);
+        opus_rs_projection_decoder_set_gain(x: opus_int32);
+        opus_rs_projection_decoder_get_gain(x: *mut opus_int32);
+        opus_rs_projection_decoder_get_pitch(x: *mut opus_int32);
+        opus_rs_projection_decoder_get_last_packet_duration(x: *mut opus_int32);
+        opus_rs_projection_decoder_reset_state();
+    }
+    OpusDREDDecoder {
+        #[cfg(opus_1_5)]
+        opus_rs_dred_decoder_set_dnn_blob(data: *const c_void, len: opus_int32);
+    }
+    OpusCustomEncoder {
+        #[cfg(feature = "custom-modes")]
+        opus_rs_custom_encoder_get_final_range(x: *mut opus_uint32);
+        #[cfg(feature = "custom-modes")]
+        opus_rs_custom_encoder_set_phase_inversion_disabled(x: opus_int32);
+        #[cfg(feature = "custom-modes")]
+        opus_rs_custom_encoder_get_phase_inversion_disabled(x: *mut opus_int32);
+        #[cfg(feature = "custom-modes")]
+        opus_rs_custom_encoder_set_bitrate(x: opus_int32);
+        #[cfg(feature = "custom-modes")]
+        opus_rs_custom_encoder_set_vbr(x: opus_int32);
+        #[cfg(feature = "custom-modes")]
+        opus_rs_custom_encoder_set_vbr_constraint(x: opus_int32);
+        #[cfg(feature = "custom-modes")]
+        opus_rs_custom_encoder_set_complexity(x: opus_int32);
+        #[cfg(feature = "custom-modes")]
+        opus_rs_custom_encoder_set_packet_loss_perc(x: opus_int32);
+        #[cfg(feature = "custom-modes")]
+        opus_rs_custom_encoder_set_lsb_depth(x: opus_int32);
+        #[cfg(feature = "custom-modes")]
+        opus_rs_custom_encoder_get_lsb_depth(x: *mut opus_int32);
+        #[cfg(feature = "custom-modes")]
+        opus_rs_custom_encoder_reset_state();
+    }
+    OpusCustomDecoder {
+        #[cfg(feature = "custom-modes")]
+        opus_rs_custom_decoder_get_final_range(x: *mut opus_uint32);
+        #[cfg(feature = "custom-modes")]
+        opus_rs_custom_decoder_set_phase_inversion_disabled(x: opus_int32);
+        #[cfg(feature = "custom-modes")]
+        opus_rs_custom_decoder_get_phase_inversion_disabled(x: *mut opus_int32);
+        #[cfg(feature = "custom-modes")]
+        opus_rs_custom_decoder_get_lookahead(x: *mut opus_int32);
+        #[cfg(feature = "custom-modes")]
+        opus_rs_custom_decoder_get_pitch(x: *mut opus_int32);
+        #[cfg(feature = "custom-modes")]
+        opus_rs_custom_decoder_reset_state();
+    }
+}
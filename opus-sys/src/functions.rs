@@ -0,0 +1,1313 @@
+/* automatically generated by rust-bindgen 0.65.1 */
+
+extern "C" {
+    pub fn opus_strerror(error: ::std::os::raw::c_int) -> *const ::std::os::raw::c_char;
+}
+extern "C" {
+    pub fn opus_get_version_string() -> *const ::std::os::raw::c_char;
+}
+extern "C" {
+    pub fn opus_encoder_get_size(channels: ::std::os::raw::c_int) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_encoder_create(
+        Fs: opus_int32,
+        channels: ::std::os::raw::c_int,
+        application: ::std::os::raw::c_int,
+        error: *mut ::std::os::raw::c_int,
+    ) -> *mut OpusEncoder;
+}
+extern "C" {
+    pub fn opus_encoder_init(
+        st: *mut OpusEncoder,
+        Fs: opus_int32,
+        channels: ::std::os::raw::c_int,
+        application: ::std::os::raw::c_int,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_encode(
+        st: *mut OpusEncoder,
+        pcm: *const opus_int16,
+        frame_size: ::std::os::raw::c_int,
+        data: *mut ::std::os::raw::c_uchar,
+        max_data_bytes: opus_int32,
+    ) -> opus_int32;
+}
+extern "C" {
+    pub fn opus_encode_float(
+        st: *mut OpusEncoder,
+        pcm: *const f32,
+        frame_size: ::std::os::raw::c_int,
+        data: *mut ::std::os::raw::c_uchar,
+        max_data_bytes: opus_int32,
+    ) -> opus_int32;
+}
+extern "C" {
+    pub fn opus_encoder_destroy(st: *mut OpusEncoder);
+}
+extern "C" {
+    pub fn opus_encoder_ctl(
+        st: *mut OpusEncoder,
+        request: ::std::os::raw::c_int,
+        ...
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_decoder_get_size(channels: ::std::os::raw::c_int) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_decoder_create(
+        Fs: opus_int32,
+        channels: ::std::os::raw::c_int,
+        error: *mut ::std::os::raw::c_int,
+    ) -> *mut OpusDecoder;
+}
+extern "C" {
+    pub fn opus_decoder_init(
+        st: *mut OpusDecoder,
+        Fs: opus_int32,
+        channels: ::std::os::raw::c_int,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_decode(
+        st: *mut OpusDecoder,
+        data: *const ::std::os::raw::c_uchar,
+        len: opus_int32,
+        pcm: *mut opus_int16,
+        frame_size: ::std::os::raw::c_int,
+        decode_fec: ::std::os::raw::c_int,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_decode_float(
+        st: *mut OpusDecoder,
+        data: *const ::std::os::raw::c_uchar,
+        len: opus_int32,
+        pcm: *mut f32,
+        frame_size: ::std::os::raw::c_int,
+        decode_fec: ::std::os::raw::c_int,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_decoder_ctl(
+        st: *mut OpusDecoder,
+        request: ::std::os::raw::c_int,
+        ...
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_decoder_destroy(st: *mut OpusDecoder);
+}
+extern "C" {
+    pub fn opus_packet_parse(
+        data: *const ::std::os::raw::c_uchar,
+        len: opus_int32,
+        out_toc: *mut ::std::os::raw::c_uchar,
+        frames: *mut *const ::std::os::raw::c_uchar,
+        size: *mut opus_int16,
+        payload_offset: *mut ::std::os::raw::c_int,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_packet_get_bandwidth(data: *const ::std::os::raw::c_uchar)
+        -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_packet_get_samples_per_frame(
+        data: *const ::std::os::raw::c_uchar,
+        Fs: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_packet_get_nb_channels(
+        data: *const ::std::os::raw::c_uchar,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_packet_get_nb_frames(
+        packet: *const ::std::os::raw::c_uchar,
+        len: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_packet_get_nb_samples(
+        packet: *const ::std::os::raw::c_uchar,
+        len: opus_int32,
+        Fs: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_decoder_get_nb_samples(
+        dec: *const OpusDecoder,
+        packet: *const ::std::os::raw::c_uchar,
+        len: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_pcm_soft_clip(
+        pcm: *mut f32,
+        frame_size: ::std::os::raw::c_int,
+        channels: ::std::os::raw::c_int,
+        softclip_mem: *mut f32,
+    );
+}
+extern "C" {
+    pub fn opus_repacketizer_get_size() -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_repacketizer_init(rp: *mut OpusRepacketizer) -> *mut OpusRepacketizer;
+}
+extern "C" {
+    pub fn opus_repacketizer_create() -> *mut OpusRepacketizer;
+}
+extern "C" {
+    pub fn opus_repacketizer_destroy(rp: *mut OpusRepacketizer);
+}
+extern "C" {
+    pub fn opus_repacketizer_cat(
+        rp: *mut OpusRepacketizer,
+        data: *const ::std::os::raw::c_uchar,
+        len: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_repacketizer_out_range(
+        rp: *mut OpusRepacketizer,
+        begin: ::std::os::raw::c_int,
+        end: ::std::os::raw::c_int,
+        data: *mut ::std::os::raw::c_uchar,
+        maxlen: opus_int32,
+    ) -> opus_int32;
+}
+extern "C" {
+    pub fn opus_repacketizer_get_nb_frames(rp: *mut OpusRepacketizer) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_repacketizer_out(
+        rp: *mut OpusRepacketizer,
+        data: *mut ::std::os::raw::c_uchar,
+        maxlen: opus_int32,
+    ) -> opus_int32;
+}
+extern "C" {
+    pub fn opus_packet_pad(
+        data: *mut ::std::os::raw::c_uchar,
+        len: opus_int32,
+        new_len: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_packet_unpad(data: *mut ::std::os::raw::c_uchar, len: opus_int32) -> opus_int32;
+}
+extern "C" {
+    pub fn opus_multistream_packet_pad(
+        data: *mut ::std::os::raw::c_uchar,
+        len: opus_int32,
+        new_len: opus_int32,
+        nb_streams: ::std::os::raw::c_int,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_multistream_packet_unpad(
+        data: *mut ::std::os::raw::c_uchar,
+        len: opus_int32,
+        nb_streams: ::std::os::raw::c_int,
+    ) -> opus_int32;
+}
+extern "C" {
+    pub fn opus_multistream_encoder_get_size(
+        streams: ::std::os::raw::c_int,
+        coupled_streams: ::std::os::raw::c_int,
+    ) -> opus_int32;
+}
+extern "C" {
+    pub fn opus_multistream_surround_encoder_get_size(
+        channels: ::std::os::raw::c_int,
+        mapping_family: ::std::os::raw::c_int,
+    ) -> opus_int32;
+}
+extern "C" {
+    pub fn opus_multistream_encoder_create(
+        Fs: opus_int32,
+        channels: ::std::os::raw::c_int,
+        streams: ::std::os::raw::c_int,
+        coupled_streams: ::std::os::raw::c_int,
+        mapping: *const ::std::os::raw::c_uchar,
+        application: ::std::os::raw::c_int,
+        error: *mut ::std::os::raw::c_int,
+    ) -> *mut OpusMSEncoder;
+}
+extern "C" {
+    pub fn opus_multistream_surround_encoder_create(
+        Fs: opus_int32,
+        channels: ::std::os::raw::c_int,
+        mapping_family: ::std::os::raw::c_int,
+        streams: *mut ::std::os::raw::c_int,
+        coupled_streams: *mut ::std::os::raw::c_int,
+        mapping: *mut ::std::os::raw::c_uchar,
+        application: ::std::os::raw::c_int,
+        error: *mut ::std::os::raw::c_int,
+    ) -> *mut OpusMSEncoder;
+}
+extern "C" {
+    pub fn opus_multistream_encoder_init(
+        st: *mut OpusMSEncoder,
+        Fs: opus_int32,
+        channels: ::std::os::raw::c_int,
+        streams: ::std::os::raw::c_int,
+        coupled_streams: ::std::os::raw::c_int,
+        mapping: *const ::std::os::raw::c_uchar,
+        application: ::std::os::raw::c_int,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_multistream_surround_encoder_init(
+        st: *mut OpusMSEncoder,
+        Fs: opus_int32,
+        channels: ::std::os::raw::c_int,
+        mapping_family: ::std::os::raw::c_int,
+        streams: *mut ::std::os::raw::c_int,
+        coupled_streams: *mut ::std::os::raw::c_int,
+        mapping: *mut ::std::os::raw::c_uchar,
+        application: ::std::os::raw::c_int,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_multistream_encode(
+        st: *mut OpusMSEncoder,
+        pcm: *const opus_int16,
+        frame_size: ::std::os::raw::c_int,
+        data: *mut ::std::os::raw::c_uchar,
+        max_data_bytes: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_multistream_encode_float(
+        st: *mut OpusMSEncoder,
+        pcm: *const f32,
+        frame_size: ::std::os::raw::c_int,
+        data: *mut ::std::os::raw::c_uchar,
+        max_data_bytes: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_multistream_encoder_destroy(st: *mut OpusMSEncoder);
+}
+extern "C" {
+    pub fn opus_multistream_encoder_ctl(
+        st: *mut OpusMSEncoder,
+        request: ::std::os::raw::c_int,
+        ...
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_multistream_decoder_get_size(
+        streams: ::std::os::raw::c_int,
+        coupled_streams: ::std::os::raw::c_int,
+    ) -> opus_int32;
+}
+extern "C" {
+    pub fn opus_multistream_decoder_create(
+        Fs: opus_int32,
+        channels: ::std::os::raw::c_int,
+        streams: ::std::os::raw::c_int,
+        coupled_streams: ::std::os::raw::c_int,
+        mapping: *const ::std::os::raw::c_uchar,
+        error: *mut ::std::os::raw::c_int,
+    ) -> *mut OpusMSDecoder;
+}
+extern "C" {
+    pub fn opus_multistream_decoder_init(
+        st: *mut OpusMSDecoder,
+        Fs: opus_int32,
+        channels: ::std::os::raw::c_int,
+        streams: ::std::os::raw::c_int,
+        coupled_streams: ::std::os::raw::c_int,
+        mapping: *const ::std::os::raw::c_uchar,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_multistream_decode(
+        st: *mut OpusMSDecoder,
+        data: *const ::std::os::raw::c_uchar,
+        len: opus_int32,
+        pcm: *mut opus_int16,
+        frame_size: ::std::os::raw::c_int,
+        decode_fec: ::std::os::raw::c_int,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_multistream_decode_float(
+        st: *mut OpusMSDecoder,
+        data: *const ::std::os::raw::c_uchar,
+        len: opus_int32,
+        pcm: *mut f32,
+        frame_size: ::std::os::raw::c_int,
+        decode_fec: ::std::os::raw::c_int,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_multistream_decoder_ctl(
+        st: *mut OpusMSDecoder,
+        request: ::std::os::raw::c_int,
+        ...
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_multistream_decoder_destroy(st: *mut OpusMSDecoder);
+}
+extern "C" {
+    pub fn opus_projection_ambisonics_encoder_get_size(
+        channels: ::std::os::raw::c_int,
+        mapping_family: ::std::os::raw::c_int,
+    ) -> opus_int32;
+}
+extern "C" {
+    pub fn opus_projection_ambisonics_encoder_create(
+        Fs: opus_int32,
+        channels: ::std::os::raw::c_int,
+        mapping_family: ::std::os::raw::c_int,
+        streams: *mut ::std::os::raw::c_int,
+        coupled_streams: *mut ::std::os::raw::c_int,
+        application: ::std::os::raw::c_int,
+        error: *mut ::std::os::raw::c_int,
+    ) -> *mut OpusProjectionEncoder;
+}
+extern "C" {
+    pub fn opus_projection_ambisonics_encoder_init(
+        st: *mut OpusProjectionEncoder,
+        Fs: opus_int32,
+        channels: ::std::os::raw::c_int,
+        mapping_family: ::std::os::raw::c_int,
+        streams: *mut ::std::os::raw::c_int,
+        coupled_streams: *mut ::std::os::raw::c_int,
+        application: ::std::os::raw::c_int,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_projection_encode(
+        st: *mut OpusProjectionEncoder,
+        pcm: *const opus_int16,
+        frame_size: ::std::os::raw::c_int,
+        data: *mut ::std::os::raw::c_uchar,
+        max_data_bytes: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_projection_encode_float(
+        st: *mut OpusProjectionEncoder,
+        pcm: *const f32,
+        frame_size: ::std::os::raw::c_int,
+        data: *mut ::std::os::raw::c_uchar,
+        max_data_bytes: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_projection_encoder_destroy(st: *mut OpusProjectionEncoder);
+}
+extern "C" {
+    pub fn opus_projection_encoder_ctl(
+        st: *mut OpusProjectionEncoder,
+        request: ::std::os::raw::c_int,
+        ...
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_projection_decoder_get_size(
+        channels: ::std::os::raw::c_int,
+        streams: ::std::os::raw::c_int,
+        coupled_streams: ::std::os::raw::c_int,
+    ) -> opus_int32;
+}
+extern "C" {
+    pub fn opus_projection_decoder_create(
+        Fs: opus_int32,
+        channels: ::std::os::raw::c_int,
+        streams: ::std::os::raw::c_int,
+        coupled_streams: ::std::os::raw::c_int,
+        demixing_matrix: *mut ::std::os::raw::c_uchar,
+        demixing_matrix_size: opus_int32,
+        error: *mut ::std::os::raw::c_int,
+    ) -> *mut OpusProjectionDecoder;
+}
+extern "C" {
+    pub fn opus_projection_decoder_init(
+        st: *mut OpusProjectionDecoder,
+        Fs: opus_int32,
+        channels: ::std::os::raw::c_int,
+        streams: ::std::os::raw::c_int,
+        coupled_streams: ::std::os::raw::c_int,
+        demixing_matrix: *mut ::std::os::raw::c_uchar,
+        demixing_matrix_size: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_projection_decode(
+        st: *mut OpusProjectionDecoder,
+        data: *const ::std::os::raw::c_uchar,
+        len: opus_int32,
+        pcm: *mut opus_int16,
+        frame_size: ::std::os::raw::c_int,
+        decode_fec: ::std::os::raw::c_int,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_projection_decode_float(
+        st: *mut OpusProjectionDecoder,
+        data: *const ::std::os::raw::c_uchar,
+        len: opus_int32,
+        pcm: *mut f32,
+        frame_size: ::std::os::raw::c_int,
+        decode_fec: ::std::os::raw::c_int,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_projection_decoder_ctl(
+        st: *mut OpusProjectionDecoder,
+        request: ::std::os::raw::c_int,
+        ...
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_projection_decoder_destroy(st: *mut OpusProjectionDecoder);
+}
+extern "C" {
+    pub fn opus_rs_encoder_get_bandwidth(
+        st: *mut OpusEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_encoder_get_sample_rate(
+        st: *mut OpusEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_encoder_get_final_range(
+        st: *mut OpusEncoder,
+        x: *mut opus_uint32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_encoder_set_phase_inversion_disabled(
+        st: *mut OpusEncoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_encoder_get_phase_inversion_disabled(
+        st: *mut OpusEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_encoder_set_application(
+        st: *mut OpusEncoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_encoder_get_application(
+        st: *mut OpusEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_encoder_set_bitrate(
+        st: *mut OpusEncoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_encoder_get_bitrate(
+        st: *mut OpusEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_encoder_set_max_bandwidth(
+        st: *mut OpusEncoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_encoder_get_max_bandwidth(
+        st: *mut OpusEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_encoder_set_vbr(st: *mut OpusEncoder, x: opus_int32) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_encoder_get_vbr(
+        st: *mut OpusEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_encoder_set_bandwidth(
+        st: *mut OpusEncoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_encoder_set_complexity(
+        st: *mut OpusEncoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_encoder_get_complexity(
+        st: *mut OpusEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_encoder_set_inband_fec(
+        st: *mut OpusEncoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_encoder_get_inband_fec(
+        st: *mut OpusEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_encoder_set_packet_loss_perc(
+        st: *mut OpusEncoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_encoder_get_packet_loss_perc(
+        st: *mut OpusEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_encoder_set_dtx(st: *mut OpusEncoder, x: opus_int32) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_encoder_get_dtx(
+        st: *mut OpusEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_encoder_set_vbr_constraint(
+        st: *mut OpusEncoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_encoder_get_vbr_constraint(
+        st: *mut OpusEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_encoder_set_force_channels(
+        st: *mut OpusEncoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_encoder_get_force_channels(
+        st: *mut OpusEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_encoder_set_signal(st: *mut OpusEncoder, x: opus_int32)
+        -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_encoder_get_signal(
+        st: *mut OpusEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_encoder_get_lookahead(
+        st: *mut OpusEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_encoder_set_lsb_depth(
+        st: *mut OpusEncoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_encoder_get_lsb_depth(
+        st: *mut OpusEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_encoder_set_expert_frame_duration(
+        st: *mut OpusEncoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_encoder_get_expert_frame_duration(
+        st: *mut OpusEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_encoder_set_prediction_disabled(
+        st: *mut OpusEncoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_encoder_get_prediction_disabled(
+        st: *mut OpusEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_encoder_reset_state(st: *mut OpusEncoder) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_decoder_get_bandwidth(
+        st: *mut OpusDecoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_decoder_get_sample_rate(
+        st: *mut OpusDecoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_decoder_get_final_range(
+        st: *mut OpusDecoder,
+        x: *mut opus_uint32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_decoder_set_phase_inversion_disabled(
+        st: *mut OpusDecoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_decoder_get_phase_inversion_disabled(
+        st: *mut OpusDecoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_decoder_set_gain(st: *mut OpusDecoder, x: opus_int32) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_decoder_get_gain(
+        st: *mut OpusDecoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_decoder_get_pitch(
+        st: *mut OpusDecoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_decoder_get_last_packet_duration(
+        st: *mut OpusDecoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_decoder_reset_state(st: *mut OpusDecoder) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_multistream_encoder_get_bandwidth(
+        st: *mut OpusMSEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_multistream_encoder_get_sample_rate(
+        st: *mut OpusMSEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_multistream_encoder_get_final_range(
+        st: *mut OpusMSEncoder,
+        x: *mut opus_uint32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_multistream_encoder_set_phase_inversion_disabled(
+        st: *mut OpusMSEncoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_multistream_encoder_get_phase_inversion_disabled(
+        st: *mut OpusMSEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_multistream_encoder_set_application(
+        st: *mut OpusMSEncoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_multistream_encoder_get_application(
+        st: *mut OpusMSEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_multistream_encoder_set_bitrate(
+        st: *mut OpusMSEncoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_multistream_encoder_get_bitrate(
+        st: *mut OpusMSEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_multistream_encoder_set_max_bandwidth(
+        st: *mut OpusMSEncoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_multistream_encoder_get_max_bandwidth(
+        st: *mut OpusMSEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_multistream_encoder_set_vbr(
+        st: *mut OpusMSEncoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_multistream_encoder_get_vbr(
+        st: *mut OpusMSEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_multistream_encoder_set_bandwidth(
+        st: *mut OpusMSEncoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_multistream_encoder_set_complexity(
+        st: *mut OpusMSEncoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_multistream_encoder_get_complexity(
+        st: *mut OpusMSEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_multistream_encoder_set_inband_fec(
+        st: *mut OpusMSEncoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_multistream_encoder_get_inband_fec(
+        st: *mut OpusMSEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_multistream_encoder_set_packet_loss_perc(
+        st: *mut OpusMSEncoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_multistream_encoder_get_packet_loss_perc(
+        st: *mut OpusMSEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_multistream_encoder_set_dtx(
+        st: *mut OpusMSEncoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_multistream_encoder_get_dtx(
+        st: *mut OpusMSEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_multistream_encoder_set_vbr_constraint(
+        st: *mut OpusMSEncoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_multistream_encoder_get_vbr_constraint(
+        st: *mut OpusMSEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_multistream_encoder_set_force_channels(
+        st: *mut OpusMSEncoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_multistream_encoder_get_force_channels(
+        st: *mut OpusMSEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_multistream_encoder_set_signal(
+        st: *mut OpusMSEncoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_multistream_encoder_get_signal(
+        st: *mut OpusMSEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_multistream_encoder_get_lookahead(
+        st: *mut OpusMSEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_multistream_encoder_set_lsb_depth(
+        st: *mut OpusMSEncoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_multistream_encoder_get_lsb_depth(
+        st: *mut OpusMSEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_multistream_encoder_set_expert_frame_duration(
+        st: *mut OpusMSEncoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_multistream_encoder_get_expert_frame_duration(
+        st: *mut OpusMSEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_multistream_encoder_set_prediction_disabled(
+        st: *mut OpusMSEncoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_multistream_encoder_get_prediction_disabled(
+        st: *mut OpusMSEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_multistream_encoder_reset_state(st: *mut OpusMSEncoder)
+        -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_multistream_decoder_get_bandwidth(
+        st: *mut OpusMSDecoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_multistream_decoder_get_sample_rate(
+        st: *mut OpusMSDecoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_multistream_decoder_get_final_range(
+        st: *mut OpusMSDecoder,
+        x: *mut opus_uint32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_multistream_decoder_set_phase_inversion_disabled(
+        st: *mut OpusMSDecoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_multistream_decoder_get_phase_inversion_disabled(
+        st: *mut OpusMSDecoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_multistream_decoder_set_gain(
+        st: *mut OpusMSDecoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_multistream_decoder_get_gain(
+        st: *mut OpusMSDecoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_multistream_decoder_get_pitch(
+        st: *mut OpusMSDecoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_multistream_decoder_get_last_packet_duration(
+        st: *mut OpusMSDecoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_multistream_decoder_reset_state(st: *mut OpusMSDecoder)
+        -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_projection_encoder_get_bandwidth(
+        st: *mut OpusProjectionEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_projection_encoder_get_sample_rate(
+        st: *mut OpusProjectionEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_projection_encoder_get_final_range(
+        st: *mut OpusProjectionEncoder,
+        x: *mut opus_uint32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_projection_encoder_set_phase_inversion_disabled(
+        st: *mut OpusProjectionEncoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_projection_encoder_get_phase_inversion_disabled(
+        st: *mut OpusProjectionEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_projection_encoder_set_application(
+        st: *mut OpusProjectionEncoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_projection_encoder_get_application(
+        st: *mut OpusProjectionEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_projection_encoder_set_bitrate(
+        st: *mut OpusProjectionEncoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_projection_encoder_get_bitrate(
+        st: *mut OpusProjectionEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_projection_encoder_set_max_bandwidth(
+        st: *mut OpusProjectionEncoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_projection_encoder_get_max_bandwidth(
+        st: *mut OpusProjectionEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_projection_encoder_set_vbr(
+        st: *mut OpusProjectionEncoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_projection_encoder_get_vbr(
+        st: *mut OpusProjectionEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_projection_encoder_set_bandwidth(
+        st: *mut OpusProjectionEncoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_projection_encoder_set_complexity(
+        st: *mut OpusProjectionEncoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_projection_encoder_get_complexity(
+        st: *mut OpusProjectionEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_projection_encoder_set_inband_fec(
+        st: *mut OpusProjectionEncoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_projection_encoder_get_inband_fec(
+        st: *mut OpusProjectionEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_projection_encoder_set_packet_loss_perc(
+        st: *mut OpusProjectionEncoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_projection_encoder_get_packet_loss_perc(
+        st: *mut OpusProjectionEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_projection_encoder_set_dtx(
+        st: *mut OpusProjectionEncoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_projection_encoder_get_dtx(
+        st: *mut OpusProjectionEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_projection_encoder_set_vbr_constraint(
+        st: *mut OpusProjectionEncoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_projection_encoder_get_vbr_constraint(
+        st: *mut OpusProjectionEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_projection_encoder_set_force_channels(
+        st: *mut OpusProjectionEncoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_projection_encoder_get_force_channels(
+        st: *mut OpusProjectionEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_projection_encoder_set_signal(
+        st: *mut OpusProjectionEncoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_projection_encoder_get_signal(
+        st: *mut OpusProjectionEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_projection_encoder_get_lookahead(
+        st: *mut OpusProjectionEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_projection_encoder_set_lsb_depth(
+        st: *mut OpusProjectionEncoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_projection_encoder_get_lsb_depth(
+        st: *mut OpusProjectionEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_projection_encoder_set_expert_frame_duration(
+        st: *mut OpusProjectionEncoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_projection_encoder_get_expert_frame_duration(
+        st: *mut OpusProjectionEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_projection_encoder_set_prediction_disabled(
+        st: *mut OpusProjectionEncoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_projection_encoder_get_prediction_disabled(
+        st: *mut OpusProjectionEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_projection_encoder_reset_state(
+        st: *mut OpusProjectionEncoder,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_projection_decoder_get_bandwidth(
+        st: *mut OpusProjectionDecoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_projection_decoder_get_sample_rate(
+        st: *mut OpusProjectionDecoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_projection_decoder_get_final_range(
+        st: *mut OpusProjectionDecoder,
+        x: *mut opus_uint32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_projection_decoder_set_phase_inversion_disabled(
+        st: *mut OpusProjectionDecoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_projection_decoder_get_phase_inversion_disabled(
+        st: *mut OpusProjectionDecoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_projection_decoder_set_gain(
+        st: *mut OpusProjectionDecoder,
+        x: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_projection_decoder_get_gain(
+        st: *mut OpusProjectionDecoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_projection_decoder_get_pitch(
+        st: *mut OpusProjectionDecoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_projection_decoder_get_last_packet_duration(
+        st: *mut OpusProjectionDecoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_projection_decoder_reset_state(
+        st: *mut OpusProjectionDecoder,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_multistream_encoder_get_encoder_state(
+        st: *mut OpusMSEncoder,
+        stream_id: opus_int32,
+        state: *mut *mut OpusEncoder,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_multistream_decoder_get_decoder_state(
+        st: *mut OpusMSDecoder,
+        stream_id: opus_int32,
+        state: *mut *mut OpusDecoder,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_projection_encoder_get_demixing_matrix_gain(
+        st: *mut OpusProjectionEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_projection_encoder_get_demixing_matrix_size(
+        st: *mut OpusProjectionEncoder,
+        x: *mut opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn opus_rs_projection_encoder_get_demixing_matrix(
+        st: *mut OpusProjectionEncoder,
+        matrix: *mut ::std::os::raw::c_uchar,
+        size: opus_int32,
+    ) -> ::std::os::raw::c_int;
+}
@@ -3,16 +3,267 @@
 #![allow(non_snake_case)]
 #![allow(non_upper_case_globals)]
 
+// The shipped bindings cover opus.h, opus_multistream.h and opus_projection.h
+// of libopus 1.3 plus the typed ctl wrappers in data/ctl.h; build with the
+// `bindgen` feature to regenerate them. The `dred` feature adds the libopus
+// 1.5 DRED and neural PLC API, `custom-modes` adds opus_custom.h.
+#[cfg(feature = "bindgen")]
 include!(concat!(env!("OUT_DIR"), "/opus.rs"));
+#[cfg(not(feature = "bindgen"))]
+include!("bindings.rs");
+
+#[cfg(all(feature = "bindgen", not(feature = "dlopen")))]
+include!(concat!(env!("OUT_DIR"), "/functions.rs"));
+#[cfg(all(not(feature = "bindgen"), not(feature = "dlopen")))]
+include!("functions.rs");
+
+// With `bindgen` the 1.5 API comes from the headers being bound
+#[cfg(all(opus_1_5, feature = "dred", not(feature = "bindgen")))]
+include!("dred_bindings.rs");
+#[cfg(all(
+    opus_1_5,
+    feature = "dred",
+    not(feature = "bindgen"),
+    not(feature = "dlopen")
+))]
+include!("dred_functions.rs");
+
+#[cfg(all(feature = "custom-modes", not(feature = "bindgen")))]
+include!("custom_bindings.rs");
+#[cfg(all(
+    feature = "custom-modes",
+    not(feature = "bindgen"),
+    not(feature = "dlopen")
+))]
+include!("custom_functions.rs");
+
+#[cfg(feature = "dlopen")]
+mod dynamic;
+#[cfg(feature = "dlopen")]
+pub use dynamic::*;
+
+/// Version of the libopus linked at build time, `None` when it is opened at
+/// runtime. The `fixed-point` build reports e.g. `1.3.1-fixed`, like
+/// `opus_get_version_string` does.
+///
+/// Within this crate the API level is also exposed as the `opus_1_4` and
+/// `opus_1_5` cfg flags, the `dlopen` build binds the 1.5 API only with the
+/// `dred` feature.
+pub const LINKED_VERSION: Option<&str> = option_env!("OPUS_SYS_LINKED_VERSION");
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::ffi::CStr;
+    use std::os::raw::c_int;
+    use std::ptr;
+
+    /// Whether there is a libopus to test, the dlopen build may run without.
+    fn available() -> bool {
+        #[cfg(feature = "dlopen")]
+        if let Err(e) = super::library() {
+            eprintln!("skipping, set {} to run: {}", LIBRARY_PATH_ENV, e);
+            return false;
+        }
+
+        true
+    }
+
+    #[cfg(feature = "dlopen")]
+    #[test]
+    fn library() {
+        assert!(unsafe { Library::open("/nonexistent/libopus.so") }.is_err());
+
+        if !available() {
+            return;
+        }
+        let lib = super::library().unwrap();
+        assert!(lib.version().starts_with("libopus"));
+        assert_eq!(
+            lib.version(),
+            unsafe { CStr::from_ptr(opus_get_version_string()) }.to_string_lossy()
+        );
+    }
+
     #[test]
     fn version() {
-        println!("{}", unsafe {
-            CStr::from_ptr(opus_get_version_string()).to_string_lossy()
-        });
+        if !available() {
+            return;
+        }
+        let version = unsafe { CStr::from_ptr(opus_get_version_string()).to_string_lossy() };
+        println!("{}", version);
+
+        if let Some(linked) = LINKED_VERSION {
+            assert!(version.contains(linked));
+        }
+        if cfg!(feature = "fixed-point") {
+            assert!(version.ends_with("-fixed"));
+        }
+    }
+
+    // A smoke test of the integer widths, error codes and request numbers
+    // against the linked library, not a full comparison of the bindings:
+    // regenerate them with `bindgen` and diff for that. The raw ctl entry
+    // points are only available when linking.
+    #[cfg(not(feature = "dlopen"))]
+    #[test]
+    fn linked_abi() {
+        use std::mem;
+
+        assert_eq!(mem::size_of::<opus_int16>(), 2);
+        assert_eq!(mem::size_of::<opus_uint16>(), 2);
+        assert_eq!(mem::size_of::<opus_int32>(), 4);
+        assert_eq!(mem::size_of::<opus_uint32>(), 4);
+
+        unsafe {
+            assert!(opus_encoder_get_size(2) > 0);
+            assert!(opus_decoder_get_size(2) > 0);
+            assert!(opus_multistream_decoder_get_size(2, 1) > 0);
+            assert!(opus_projection_ambisonics_encoder_get_size(4, 3) > 0);
+
+            // The error codes and request numbers must match the linked library
+            let mut err = 0;
+            let dec = opus_decoder_create(48000, 3, &mut err);
+            assert!(dec.is_null());
+            assert_eq!(err, OPUS_BAD_ARG);
+
+            let enc = opus_encoder_create(48000, 2, OPUS_APPLICATION_AUDIO as c_int, &mut err);
+            assert_eq!(err, OPUS_OK as c_int);
+            let ret = opus_encoder_ctl(enc, OPUS_SET_BITRATE_REQUEST as c_int, 64000 as opus_int32);
+            assert_eq!(ret, OPUS_OK as c_int);
+            let mut bitrate: opus_int32 = 0;
+            opus_encoder_ctl(
+                enc,
+                OPUS_GET_BITRATE_REQUEST as c_int,
+                &mut bitrate as *mut _,
+            );
+            assert_eq!(bitrate, 64000);
+            opus_encoder_destroy(enc);
+
+            let mut streams = 0;
+            let mut coupled = 0;
+            let enc = opus_projection_ambisonics_encoder_create(
+                48000,
+                4,
+                3,
+                &mut streams,
+                &mut coupled,
+                OPUS_APPLICATION_AUDIO as c_int,
+                &mut err,
+            );
+            assert_eq!(err, OPUS_OK as c_int);
+
+            let mut size: opus_int32 = 0;
+            opus_projection_encoder_ctl(
+                enc,
+                OPUS_PROJECTION_GET_DEMIXING_MATRIX_SIZE_REQUEST as c_int,
+                &mut size as *mut _,
+            );
+            assert_eq!(size as usize, 4 * (streams + coupled) as usize * 2);
+
+            let mut matrix = vec![0u8; size as usize];
+            let ret = opus_projection_encoder_ctl(
+                enc,
+                OPUS_PROJECTION_GET_DEMIXING_MATRIX_REQUEST as c_int,
+                matrix.as_mut_ptr(),
+                size,
+            );
+            assert_eq!(ret, OPUS_OK as c_int);
+
+            let pcm = vec![0i16; 960 * 4];
+            let mut packet = vec![0u8; 4000];
+            let len = opus_projection_encode(
+                enc,
+                pcm.as_ptr(),
+                960,
+                packet.as_mut_ptr(),
+                packet.len() as opus_int32,
+            );
+            assert!(len > 0);
+            opus_projection_encoder_destroy(enc);
+
+            let dec = opus_projection_decoder_create(
+                48000,
+                4,
+                streams,
+                coupled,
+                matrix.as_mut_ptr(),
+                size,
+                &mut err,
+            );
+            assert_eq!(err, OPUS_OK as c_int);
+
+            let mut out = vec![0f32; 960 * 4];
+            let samples =
+                opus_projection_decode_float(dec, packet.as_ptr(), len, out.as_mut_ptr(), 960, 0);
+            assert_eq!(samples, 960);
+
+            let mut rate: opus_int32 = 0;
+            opus_projection_decoder_ctl(
+                dec,
+                OPUS_GET_SAMPLE_RATE_REQUEST as c_int,
+                &mut rate as *mut _,
+            );
+            assert_eq!(rate, 48000);
+            opus_projection_decoder_destroy(dec);
+        }
+    }
+
+    #[test]
+    fn ctl() {
+        if !available() {
+            return;
+        }
+        unsafe {
+            let mut err = 0;
+            let mapping = [0u8, 1];
+            let enc = opus_multistream_encoder_create(
+                48000,
+                2,
+                1,
+                1,
+                mapping.as_ptr(),
+                OPUS_APPLICATION_AUDIO as c_int,
+                &mut err,
+            );
+            assert_eq!(err, OPUS_OK as c_int);
+
+            assert_eq!(opus_rs_multistream_encoder_set_bitrate(enc, 96000), 0);
+            assert_eq!(opus_rs_multistream_encoder_set_bitrate(enc, OPUS_AUTO), 0);
+            assert_eq!(
+                opus_rs_multistream_encoder_set_complexity(enc, 11),
+                OPUS_BAD_ARG
+            );
+
+            let mut stream = ptr::null_mut();
+            assert_eq!(
+                opus_rs_multistream_encoder_get_encoder_state(enc, 0, &mut stream),
+                0
+            );
+            assert_eq!(opus_rs_encoder_set_complexity(stream, 3), 0);
+            let mut val = 0;
+            assert_eq!(opus_rs_multistream_encoder_get_complexity(enc, &mut val), 0);
+            assert_eq!(val, 3);
+
+            let mut range = 0u32;
+            assert_eq!(
+                opus_rs_multistream_encoder_get_final_range(enc, &mut range),
+                0
+            );
+            assert_eq!(opus_rs_multistream_encoder_reset_state(enc), 0);
+            opus_multistream_encoder_destroy(enc);
+
+            let dec = opus_multistream_decoder_create(48000, 2, 1, 1, mapping.as_ptr(), &mut err);
+            assert_eq!(err, OPUS_OK as c_int);
+            assert_eq!(opus_rs_multistream_decoder_set_gain(dec, -256), 0);
+            assert_eq!(opus_rs_multistream_decoder_get_gain(dec, &mut val), 0);
+            assert_eq!(val, -256);
+            assert_eq!(
+                opus_rs_multistream_decoder_get_sample_rate(dec, &mut val),
+                0
+            );
+            assert_eq!(val, 48000);
+            opus_multistream_decoder_destroy(dec);
+        }
     }
 }
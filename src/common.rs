@@ -1,7 +1,8 @@
+use crate::planar::{deinterleave, interleave, Sample, Scratch};
 use ffi::*;
-use std::fmt;
 use std::ffi::CStr;
-use std::i32;
+use std::fmt;
+use std::mem::MaybeUninit;
 
 #[repr(i32)]
 #[derive(Copy, Clone, Debug)]
@@ -16,9 +17,29 @@ pub enum ErrorCode {
     Unknown = i32::MAX,
 }
 
+impl ErrorCode {
+    /// The `opus_strerror` text, for when libopus is not loaded.
+    fn description(self) -> &'static str {
+        use self::ErrorCode::*;
+        match self {
+            BadArg => "invalid argument",
+            BufferTooSmall => "buffer too small",
+            InternalError => "internal error",
+            InvalidPacket => "corrupted stream",
+            Unimplemented => "request not implemented",
+            InvalidState => "invalid state",
+            AllocFail => "memory allocation failed",
+            Unknown => "unknown error",
+        }
+    }
+}
+
 impl fmt::Display for ErrorCode {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let v = *self;
+        if loaded(Operation::Create).is_err() {
+            return write!(f, "{}", v.description());
+        }
         let s = unsafe { CStr::from_ptr(opus_strerror(v as i32)) };
         write!(f, "{}", s.to_string_lossy())
     }
@@ -40,9 +61,282 @@ impl From<i32> for ErrorCode {
     }
 }
 
+/// Operation an `Error` originates from.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Operation {
+    Create,
+    Encode,
+    Decode,
+    /// A ctl call, with its `OPUS_*_REQUEST` value
+    Ctl(u32),
+    /// A ctl call on the state of a single stream, with the stream index
+    StreamCtl(u32, usize),
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Operation::Create => write!(f, "create"),
+            Operation::Encode => write!(f, "encode"),
+            Operation::Decode => write!(f, "decode"),
+            Operation::Ctl(request) => write!(f, "ctl request {}", request),
+            Operation::StreamCtl(request, stream) => {
+                write!(f, "ctl request {} on stream {}", request, stream)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// libopus failed with the given return code.
+    Opus { op: Operation, code: i32 },
+    /// The arguments were rejected before calling into libopus.
+    Invalid { op: Operation, reason: &'static str },
+}
+
+impl Error {
+    pub(crate) fn opus(op: Operation, code: i32) -> Self {
+        Error::Opus { op, code }
+    }
+
+    pub(crate) fn invalid(op: Operation, reason: &'static str) -> Self {
+        Error::Invalid { op, reason }
+    }
+
+    pub fn operation(&self) -> Operation {
+        match *self {
+            Error::Opus { op, .. } | Error::Invalid { op, .. } => op,
+        }
+    }
+
+    /// The raw libopus return code, if libopus reported the error.
+    pub fn raw_code(&self) -> Option<i32> {
+        match *self {
+            Error::Opus { code, .. } => Some(code),
+            Error::Invalid { .. } => None,
+        }
+    }
+
+    /// The libopus error, validation errors are reported as `BadArg`.
+    pub fn code(&self) -> ErrorCode {
+        match *self {
+            Error::Opus { code, .. } => code.into(),
+            Error::Invalid { .. } => ErrorCode::BadArg,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Opus { op, code } if loaded(op).is_ok() => {
+                let s = unsafe { CStr::from_ptr(opus_strerror(code)) };
+                write!(f, "{}: {} ({})", op, s.to_string_lossy(), code)
+            }
+            Error::Opus { op, code } => write!(f, "{}: error {}", op, code),
+            Error::Invalid { op, reason } => write!(f, "{}: {}", op, reason),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Fails if libopus could not be opened at runtime.
+#[cfg(feature = "dlopen")]
+pub(crate) fn loaded(op: Operation) -> Result<(), Error> {
+    match ffi::library() {
+        Ok(_) => Ok(()),
+        Err(_) => Err(Error::invalid(op, "libopus could not be loaded")),
+    }
+}
+
+#[cfg(not(feature = "dlopen"))]
+pub(crate) fn loaded(_op: Operation) -> Result<(), Error> {
+    Ok(())
+}
+
+/// A libopus release, as reported by `opus_get_version_string`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Version {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+    /// Whatever follows the release number, e.g. `-fixed` or `-rc1`
+    pub extra: String,
+}
+
+impl Version {
+    /// Parses strings such as `libopus 1.3.1` or `libopus 1.5-fixed`.
+    pub fn parse(s: &str) -> Option<Version> {
+        let s = s.trim();
+        let s = s.strip_prefix("libopus ").unwrap_or(s);
+        let end = s
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(s.len());
+        let (number, extra) = s.split_at(end);
+
+        let mut parts = number.split('.').map(|n| n.parse::<u32>());
+        let major = parts.next()?.ok()?;
+        let minor = parts.next()?.ok()?;
+        let patch = match parts.next() {
+            Some(n) => n.ok()?,
+            None => 0,
+        };
+
+        Some(Version {
+            major,
+            minor,
+            patch,
+            extra: extra.to_owned(),
+        })
+    }
+
+    /// Whether libopus was built with `FIXED_POINT`.
+    pub fn is_fixed_point(&self) -> bool {
+        self.extra.contains("-fixed")
+    }
+
+    /// Whether this release provides the API behind `cap`.
+    pub fn supports(&self, cap: Capability) -> bool {
+        (self.major, self.minor) >= cap.since()
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}.{}.{}{}",
+            self.major, self.minor, self.patch, self.extra
+        )
+    }
+}
+
+/// Parts of the libopus API that are not available in every release.
+///
+/// Some of them also depend on how libopus was configured: DRED, deep PLC
+/// and OSCE need the matching `--enable-*` options and the controls fail
+/// with `Unimplemented` otherwise.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Capability {
+    /// `OPUS_SET_PHASE_INVERSION_DISABLED`
+    PhaseInversionControl,
+    /// Ambisonics through the projection API
+    Projection,
+    /// Deep REDundancy
+    Dred,
+    /// Neural packet loss concealment
+    DeepPlc,
+    /// Opus Speech Coding Enhancement
+    Osce,
+    /// `opus_encode24` and `opus_decode24`
+    Int24,
+    /// Reading and writing packet extensions
+    PacketExtensions,
+}
+
+impl Capability {
+    /// The first release providing it, as `(major, minor)`.
+    pub fn since(self) -> (u32, u32) {
+        match self {
+            Capability::PhaseInversionControl => (1, 2),
+            Capability::Projection => (1, 3),
+            Capability::Dred | Capability::DeepPlc | Capability::Osce => (1, 5),
+            Capability::Int24 | Capability::PacketExtensions => (1, 6),
+        }
+    }
+}
+
+/// The version of the libopus in use, `None` if it cannot be loaded or
+/// reports a version string that cannot be parsed.
+pub fn version() -> Option<Version> {
+    loaded(Operation::Create).ok()?;
+
+    let s = unsafe { CStr::from_ptr(opus_get_version_string()) };
+
+    Version::parse(&s.to_string_lossy())
+}
+
+/// Whether the libopus in use provides the API behind `cap`.
+pub fn supports(cap: Capability) -> bool {
+    match version() {
+        Some(v) => v.supports(cap),
+        None => false,
+    }
+}
+
+#[cfg(feature = "codec-trait")]
+impl From<Error> for codec::error::Error {
+    fn from(e: Error) -> Self {
+        use codec::error::Error as CodecError;
+
+        match (e.operation(), e.code()) {
+            (_, ErrorCode::Unimplemented) => CodecError::Unsupported(e.to_string()),
+            (Operation::Create, _) | (Operation::Ctl(_), _) | (Operation::StreamCtl(..), _) => {
+                CodecError::ConfigurationInvalid
+            }
+            _ => CodecError::InvalidData,
+        }
+    }
+}
+
+/// Samples to encode, interleaved or with one plane per channel.
 pub enum AudioBuffer<'a> {
     F32(&'a [f32]),
     I16(&'a [i16]),
+    F32Planar(&'a [&'a [f32]]),
+    I16Planar(&'a [&'a [i16]]),
+}
+
+impl<'a> AudioBuffer<'a> {
+    /// Number of samples, summed over all the channels.
+    pub fn len(&self) -> usize {
+        match *self {
+            AudioBuffer::F32(v) => v.len(),
+            AudioBuffer::I16(v) => v.len(),
+            AudioBuffer::F32Planar(p) => p.iter().map(|v| v.len()).sum(),
+            AudioBuffer::I16Planar(p) => p.iter().map(|v| v.len()).sum(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Passes the samples interleaved to `f32` or `i16`, planar buffers
+    /// are interleaved into `scratch` first.
+    pub(crate) fn interleaved<R, F, G>(
+        self,
+        scratch: &mut Scratch,
+        channels: usize,
+        op: Operation,
+        f32: F,
+        i16: G,
+    ) -> Result<R, Error>
+    where
+        F: FnOnce(&[f32]) -> R,
+        G: FnOnce(&[i16]) -> R,
+    {
+        fn copy<'s, T: Sample>(
+            planes: &[&[T]],
+            scratch: &'s mut Scratch,
+            channels: usize,
+            op: Operation,
+        ) -> Result<&'s [T], Error> {
+            let len = plane_len(planes.iter().map(|v| v.len()), channels, op)?;
+            let out = scratch.get(len * channels);
+            interleave(planes, out);
+            Ok(out)
+        }
+
+        Ok(match self {
+            AudioBuffer::F32(v) => f32(v),
+            AudioBuffer::I16(v) => i16(v),
+            AudioBuffer::F32Planar(p) => f32(copy(p, scratch, channels, op)?),
+            AudioBuffer::I16Planar(p) => i16(copy(p, scratch, channels, op)?),
+        })
+    }
 }
 
 impl<'a> From<&'a [i16]> for AudioBuffer<'a> {
@@ -57,19 +351,315 @@ impl<'a> From<&'a [f32]> for AudioBuffer<'a> {
     }
 }
 
-pub enum AudioBufferMut<'a> {
+impl<'a> From<&'a [&'a [i16]]> for AudioBuffer<'a> {
+    fn from(v: &'a [&'a [i16]]) -> Self {
+        AudioBuffer::I16Planar(v)
+    }
+}
+
+impl<'a> From<&'a [&'a [f32]]> for AudioBuffer<'a> {
+    fn from(v: &'a [&'a [f32]]) -> Self {
+        AudioBuffer::F32Planar(v)
+    }
+}
+
+/// Room for decoded samples, interleaved or with one plane per channel.
+///
+/// The planes are filled from their start, as many samples as decoded.
+pub enum AudioBufferMut<'a, 'b> {
     F32(&'a mut [f32]),
     I16(&'a mut [i16]),
+    F32Planar(&'a mut [&'b mut [f32]]),
+    I16Planar(&'a mut [&'b mut [i16]]),
 }
 
-impl<'a> From<&'a mut [f32]> for AudioBufferMut<'a> {
+impl AudioBufferMut<'_, '_> {
+    /// Number of samples, summed over all the channels.
+    pub fn len(&self) -> usize {
+        match *self {
+            AudioBufferMut::F32(ref v) => v.len(),
+            AudioBufferMut::I16(ref v) => v.len(),
+            AudioBufferMut::F32Planar(ref p) => p.iter().map(|v| v.len()).sum(),
+            AudioBufferMut::I16Planar(ref p) => p.iter().map(|v| v.len()).sum(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Lets `f32` or `i16` decode into an interleaved buffer, planar buffers
+    /// get `scratch` split into the planes afterwards.
+    ///
+    /// The closures return the libopus result, the samples decoded per
+    /// channel or an error code.
+    pub(crate) fn interleaved<F, G>(
+        self,
+        scratch: &mut Scratch,
+        channels: usize,
+        op: Operation,
+        f32: F,
+        i16: G,
+    ) -> Result<i32, Error>
+    where
+        F: FnOnce(&mut [f32]) -> i32,
+        G: FnOnce(&mut [i16]) -> i32,
+    {
+        fn split<T: Sample, F>(
+            planes: &mut [&mut [T]],
+            scratch: &mut Scratch,
+            channels: usize,
+            op: Operation,
+            f: F,
+        ) -> Result<i32, Error>
+        where
+            F: FnOnce(&mut [T]) -> i32,
+        {
+            let len = plane_len(planes.iter().map(|v| v.len()), channels, op)?;
+            let samples = scratch.get(len * channels);
+            let ret = f(samples);
+            if ret > 0 {
+                deinterleave(&samples[..ret as usize * channels], planes);
+            }
+            Ok(ret)
+        }
+
+        match self {
+            AudioBufferMut::F32(v) => Ok(f32(v)),
+            AudioBufferMut::I16(v) => Ok(i16(v)),
+            AudioBufferMut::F32Planar(p) => split(p, scratch, channels, op, f32),
+            AudioBufferMut::I16Planar(p) => split(p, scratch, channels, op, i16),
+        }
+    }
+}
+
+impl<'a> From<&'a mut [f32]> for AudioBufferMut<'a, '_> {
     fn from(v: &'a mut [f32]) -> Self {
         AudioBufferMut::F32(v)
     }
 }
 
-impl<'a> From<&'a mut [i16]> for AudioBufferMut<'a> {
+impl<'a> From<&'a mut [i16]> for AudioBufferMut<'a, '_> {
     fn from(v: &'a mut [i16]) -> Self {
         AudioBufferMut::I16(v)
     }
 }
+
+impl<'a, 'b> From<&'a mut [&'b mut [f32]]> for AudioBufferMut<'a, 'b> {
+    fn from(v: &'a mut [&'b mut [f32]]) -> Self {
+        AudioBufferMut::F32Planar(v)
+    }
+}
+
+impl<'a, 'b> From<&'a mut [&'b mut [i16]]> for AudioBufferMut<'a, 'b> {
+    fn from(v: &'a mut [&'b mut [i16]]) -> Self {
+        AudioBufferMut::I16Planar(v)
+    }
+}
+
+/// Checks there is a plane per channel, all of the same length, and
+/// returns that length.
+fn plane_len<I>(mut lens: I, channels: usize, op: Operation) -> Result<usize, Error>
+where
+    I: ExactSizeIterator<Item = usize>,
+{
+    if lens.len() != channels || channels == 0 {
+        return Err(Error::invalid(op, "one plane per channel expected"));
+    }
+
+    let len = lens.next().unwrap_or(0);
+    if lens.any(|l| l != len) {
+        Err(Error::invalid(op, "planes differ in length"))
+    } else {
+        Ok(len)
+    }
+}
+
+/// Alignment libopus expects from the memory of a state.
+const STATE_ALIGN: usize = 16;
+
+#[repr(C, align(16))]
+#[derive(Clone, Copy)]
+struct StateBlock([u8; STATE_ALIGN]);
+
+enum Storage {
+    Owned(Box<[StateBlock]>),
+    Supplied(Box<[MaybeUninit<u8>]>),
+}
+
+/// Aligned memory holding a libopus state, allocated by Rust or supplied
+/// by the caller.
+///
+/// Encoders and decoders created in it do not allocate, the memory can be
+/// recovered with `into_memory` and reused.
+pub struct StateMemory(Storage);
+
+impl StateMemory {
+    /// Allocates zeroed memory for a state of `size` bytes.
+    pub fn new(size: usize) -> Self {
+        let blocks = size.div_ceil(STATE_ALIGN);
+        StateMemory(Storage::Owned(
+            vec![StateBlock([0; STATE_ALIGN]); blocks].into_boxed_slice(),
+        ))
+    }
+
+    /// Takes over `buf` instead of allocating, e.g. a buffer recycled from
+    /// elsewhere in the application.
+    ///
+    /// Fails unless `buf` starts on a 16 bytes boundary.
+    pub fn from_boxed(buf: Box<[MaybeUninit<u8>]>) -> Result<Self, Error> {
+        if buf.as_ptr().align_offset(STATE_ALIGN) != 0 {
+            return Err(Error::invalid(
+                Operation::Create,
+                "state memory not aligned to 16 bytes",
+            ));
+        }
+
+        Ok(StateMemory(Storage::Supplied(buf)))
+    }
+
+    pub fn len(&self) -> usize {
+        match self.0 {
+            Storage::Owned(ref blocks) => blocks.len() * STATE_ALIGN,
+            Storage::Supplied(ref buf) => buf.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub(crate) fn as_mut_ptr(&mut self) -> *mut u8 {
+        match self.0 {
+            Storage::Owned(ref mut blocks) => blocks.as_mut_ptr() as *mut u8,
+            Storage::Supplied(ref mut buf) => buf.as_mut_ptr() as *mut u8,
+        }
+    }
+}
+
+#[cfg(feature = "codec-trait")]
+pub(crate) mod channel_map {
+    use data::audiosample::ChannelMap;
+    use data::audiosample::ChannelType::{self, *};
+
+    /// Channel orders of the Vorbis layouts used by mapping family 1
+    /// (RFC 7845, section 5.1.1.2).
+    const VORBIS_LAYOUTS: [&[ChannelType]; 8] = [
+        &[C],
+        &[L, R],
+        &[L, C, R],
+        &[L, R, Ls, Rs],
+        &[L, C, R, Ls, Rs],
+        &[L, C, R, Ls, Rs, LFE],
+        &[L, C, R, Lss, Rss, Cs, LFE],
+        &[L, C, R, Lss, Rss, Ls, Rs, LFE],
+    ];
+
+    /// Returns the Vorbis channel map for `channels`, if one is defined.
+    pub fn vorbis_map(channels: usize) -> Option<ChannelMap> {
+        if channels == 0 || channels > VORBIS_LAYOUTS.len() {
+            return None;
+        }
+
+        let mut map = ChannelMap::new();
+        map.add_channels(VORBIS_LAYOUTS[channels - 1]);
+
+        Some(map)
+    }
+
+    /// A map of `channels` channels without a defined position, as coded
+    /// by the mapping families other than 0 and 1.
+    ///
+    /// `ChannelType` has no variant for them, they are all labeled `C` and
+    /// only their order is meaningful.
+    pub fn discrete_map(channels: usize) -> ChannelMap {
+        let mut map = ChannelMap::new();
+        for _ in 0..channels {
+            map.add_channel(C);
+        }
+
+        map
+    }
+
+    /// Matches `map` against the Vorbis layout with the same number of
+    /// channels.
+    ///
+    /// Returns for each Vorbis channel its index in `map`, or `None` if
+    /// `map` is not a permutation of a Vorbis layout.
+    pub fn vorbis_order(map: &ChannelMap) -> Option<Vec<usize>> {
+        let channels = map.len();
+        if channels == 0 || channels > VORBIS_LAYOUTS.len() {
+            return None;
+        }
+
+        VORBIS_LAYOUTS[channels - 1]
+            .iter()
+            .map(|&ch| map.find_channel_id(ch).map(|id| id as usize))
+            .collect()
+    }
+}
+
+/// Whether there is a libopus to test, the dlopen build may run without.
+#[cfg(test)]
+pub(crate) fn available() -> bool {
+    #[cfg(feature = "dlopen")]
+    if let Err(e) = ffi::library() {
+        eprintln!("skipping, set {} to run: {}", ffi::LIBRARY_PATH_ENV, e);
+        return false;
+    }
+
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn version_detection() {
+        let v = Version::parse("libopus 1.3.1").unwrap();
+        assert_eq!((v.major, v.minor, v.patch), (1, 3, 1));
+        assert!(!v.is_fixed_point());
+        assert!(v.supports(Capability::Projection));
+        assert!(!v.supports(Capability::Dred));
+
+        let v = Version::parse("libopus 1.5-fixed").unwrap();
+        assert_eq!((v.major, v.minor, v.patch), (1, 5, 0));
+        assert!(v.is_fixed_point());
+        assert!(v.supports(Capability::Dred));
+        assert_eq!(v.to_string(), "1.5.0-fixed");
+
+        assert!(Version::parse("libopus unknown").is_none());
+
+        if !available() {
+            return;
+        }
+        let v = version().unwrap();
+        assert!(v.supports(Capability::Projection));
+        if cfg!(feature = "fixed-point") {
+            assert!(v.is_fixed_point());
+        }
+        assert!(supports(Capability::PhaseInversionControl));
+    }
+
+    #[test]
+    fn planar_validation() {
+        let plane = [0i16; 4];
+        let short = [0i16; 3];
+
+        let mut scratch = Scratch::default();
+        let mut interleave = |planes: &[&[i16]], channels| {
+            AudioBuffer::from(planes).interleaved(
+                &mut scratch,
+                channels,
+                Operation::Encode,
+                |_| 0,
+                |v| v.len(),
+            )
+        };
+        assert_eq!(interleave(&[&plane, &plane], 2).unwrap(), 8);
+        assert!(interleave(&[&plane, &short], 2).is_err());
+        assert!(interleave(&[&plane], 2).is_err());
+        assert!(interleave(&[], 0).is_err());
+    }
+}
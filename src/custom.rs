@@ -0,0 +1,361 @@
+//! Opus Custom, CELT with arbitrary sample rates and frame sizes.
+//!
+//! The packets are not compatible with regular Opus decoders. libopus must
+//! be configured with `--enable-custom-modes`, as the `vendored` build does
+//! when the `custom-modes` feature is enabled.
+
+use crate::common::*;
+use crate::ffi::*;
+use crate::planar::Scratch;
+
+use std::ptr;
+
+/// The largest packet Opus Custom produces.
+pub const MAX_PACKET_SIZE: usize = 1275;
+
+/// A sample rate and frame size pair, shared by the encoders and decoders
+/// created from it.
+pub struct CustomMode {
+    mode: *mut OpusCustomMode,
+    sample_rate: usize,
+    frame_size: usize,
+}
+
+// The mode is never modified after creation
+unsafe impl Send for CustomMode {}
+unsafe impl Sync for CustomMode {}
+
+impl CustomMode {
+    /// Creates a mode coding `frame_size` samples per packet at
+    /// `sample_rate`, between 8 and 96 kHz.
+    ///
+    /// Frame sizes must be even, between 40 and 1024 samples. Long frames
+    /// also need to be divisible by a power of two, multiples of 16 are
+    /// always accepted.
+    pub fn create(sample_rate: usize, frame_size: usize) -> Result<CustomMode, Error> {
+        loaded(Operation::Create)?;
+
+        let mut err = 0;
+        let mode =
+            unsafe { opus_custom_mode_create(sample_rate as i32, frame_size as i32, &mut err) };
+
+        if err < 0 {
+            Err(Error::opus(Operation::Create, err))
+        } else {
+            Ok(CustomMode {
+                mode,
+                sample_rate,
+                frame_size,
+            })
+        }
+    }
+
+    pub fn sample_rate(&self) -> usize {
+        self.sample_rate
+    }
+
+    /// Samples per channel in every packet.
+    pub fn frame_size(&self) -> usize {
+        self.frame_size
+    }
+}
+
+impl Drop for CustomMode {
+    fn drop(&mut self) {
+        unsafe { opus_custom_mode_destroy(self.mode) }
+    }
+}
+
+pub struct CustomEncoder<'a> {
+    enc: *mut OpusCustomEncoder,
+    mode: &'a CustomMode,
+    channels: usize,
+    scratch: Scratch,
+}
+
+unsafe impl Send for CustomEncoder<'_> {}
+
+impl<'a> CustomEncoder<'a> {
+    pub fn create(mode: &'a CustomMode, channels: usize) -> Result<CustomEncoder<'a>, Error> {
+        let mut err = 0;
+        let enc = unsafe { opus_custom_encoder_create(mode.mode, channels as i32, &mut err) };
+
+        if err < 0 {
+            Err(Error::opus(Operation::Create, err))
+        } else {
+            Ok(CustomEncoder {
+                enc,
+                mode,
+                channels,
+                scratch: Scratch::default(),
+            })
+        }
+    }
+
+    pub fn mode(&self) -> &'a CustomMode {
+        self.mode
+    }
+
+    pub fn channels(&self) -> usize {
+        self.channels
+    }
+
+    /// Encodes exactly one frame of the mode.
+    ///
+    /// Unless a bitrate is set, every packet is as large as `output`, up to
+    /// `MAX_PACKET_SIZE` bytes.
+    pub fn encode<'b, I>(&mut self, input: I, output: &mut [u8]) -> Result<usize, Error>
+    where
+        I: Into<AudioBuffer<'b>>,
+    {
+        let input = input.into();
+        if input.len() != self.mode.frame_size * self.channels {
+            return Err(Error::invalid(Operation::Encode, "input is not one frame"));
+        }
+
+        let enc = self.enc;
+        let (data, max) = (
+            output.as_mut_ptr(),
+            output.len().min(MAX_PACKET_SIZE) as i32,
+        );
+        let frame_size = self.mode.frame_size as i32;
+        let ret = input.interleaved(
+            &mut self.scratch,
+            self.channels,
+            Operation::Encode,
+            |v| unsafe { opus_custom_encode_float(enc, v.as_ptr(), frame_size, data, max) },
+            |v| unsafe { opus_custom_encode(enc, v.as_ptr(), frame_size, data, max) },
+        )?;
+
+        if ret < 0 {
+            Err(Error::opus(Operation::Encode, ret))
+        } else {
+            Ok(ret as usize)
+        }
+    }
+
+    pub fn set_option(&mut self, key: u32, val: i32) -> Result<(), Error> {
+        let enc = self.enc;
+        let ret = unsafe {
+            match key {
+                OPUS_SET_BITRATE_REQUEST => opus_rs_custom_encoder_set_bitrate(enc, val),
+                OPUS_SET_VBR_REQUEST => opus_rs_custom_encoder_set_vbr(enc, val),
+                OPUS_SET_VBR_CONSTRAINT_REQUEST => {
+                    opus_rs_custom_encoder_set_vbr_constraint(enc, val)
+                }
+                OPUS_SET_COMPLEXITY_REQUEST => opus_rs_custom_encoder_set_complexity(enc, val),
+                OPUS_SET_PACKET_LOSS_PERC_REQUEST => {
+                    opus_rs_custom_encoder_set_packet_loss_perc(enc, val)
+                }
+                OPUS_SET_LSB_DEPTH_REQUEST => opus_rs_custom_encoder_set_lsb_depth(enc, val),
+                OPUS_SET_PHASE_INVERSION_DISABLED_REQUEST => {
+                    opus_rs_custom_encoder_set_phase_inversion_disabled(enc, val)
+                }
+                _ => return Err(Error::invalid(Operation::Ctl(key), "unsupported request")),
+            }
+        };
+
+        if ret < 0 {
+            Err(Error::opus(Operation::Ctl(key), ret))
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn get_option(&self, key: u32) -> Result<i32, Error> {
+        let enc = self.enc;
+        let mut val: i32 = 0;
+        let ret = unsafe {
+            let v = &mut val as *mut i32;
+            match key {
+                OPUS_GET_LSB_DEPTH_REQUEST => opus_rs_custom_encoder_get_lsb_depth(enc, v),
+                OPUS_GET_FINAL_RANGE_REQUEST => {
+                    opus_rs_custom_encoder_get_final_range(enc, v as *mut u32)
+                }
+                OPUS_GET_PHASE_INVERSION_DISABLED_REQUEST => {
+                    opus_rs_custom_encoder_get_phase_inversion_disabled(enc, v)
+                }
+                _ => return Err(Error::invalid(Operation::Ctl(key), "unsupported request")),
+            }
+        };
+
+        if ret < 0 {
+            Err(Error::opus(Operation::Ctl(key), ret))
+        } else {
+            Ok(val)
+        }
+    }
+
+    pub fn reset(&mut self) {
+        let _ = unsafe { opus_rs_custom_encoder_reset_state(self.enc) };
+    }
+}
+
+impl Drop for CustomEncoder<'_> {
+    fn drop(&mut self) {
+        unsafe { opus_custom_encoder_destroy(self.enc) }
+    }
+}
+
+pub struct CustomDecoder<'a> {
+    dec: *mut OpusCustomDecoder,
+    mode: &'a CustomMode,
+    channels: usize,
+    scratch: Scratch,
+}
+
+unsafe impl Send for CustomDecoder<'_> {}
+
+impl<'a> CustomDecoder<'a> {
+    pub fn create(mode: &'a CustomMode, channels: usize) -> Result<CustomDecoder<'a>, Error> {
+        let mut err = 0;
+        let dec = unsafe { opus_custom_decoder_create(mode.mode, channels as i32, &mut err) };
+
+        if err < 0 {
+            Err(Error::opus(Operation::Create, err))
+        } else {
+            Ok(CustomDecoder {
+                dec,
+                mode,
+                channels,
+                scratch: Scratch::default(),
+            })
+        }
+    }
+
+    pub fn mode(&self) -> &'a CustomMode {
+        self.mode
+    }
+
+    pub fn channels(&self) -> usize {
+        self.channels
+    }
+
+    /// Decodes one packet into `out`, which must hold a frame of the mode.
+    ///
+    /// `None` conceals a lost packet.
+    pub fn decode<'b, 'c, 'd: 'c, I, O>(&mut self, input: I, out: O) -> Result<usize, Error>
+    where
+        I: Into<Option<&'b [u8]>>,
+        O: Into<AudioBufferMut<'c, 'd>>,
+    {
+        let (data, len) = input
+            .into()
+            .map_or((ptr::null(), 0), |v| (v.as_ptr(), v.len()));
+
+        let out = out.into();
+        if out.len() < self.mode.frame_size * self.channels {
+            return Err(Error::invalid(
+                Operation::Decode,
+                "output shorter than a frame",
+            ));
+        }
+
+        let dec = self.dec;
+        let frame_size = self.mode.frame_size as i32;
+        let ret = out.interleaved(
+            &mut self.scratch,
+            self.channels,
+            Operation::Decode,
+            |v| unsafe {
+                opus_custom_decode_float(dec, data, len as i32, v.as_mut_ptr(), frame_size)
+            },
+            |v| unsafe { opus_custom_decode(dec, data, len as i32, v.as_mut_ptr(), frame_size) },
+        )?;
+
+        if ret < 0 {
+            Err(Error::opus(Operation::Decode, ret))
+        } else {
+            Ok(ret as usize)
+        }
+    }
+
+    pub fn set_option(&mut self, key: u32, val: i32) -> Result<(), Error> {
+        let ret = match key {
+            OPUS_SET_PHASE_INVERSION_DISABLED_REQUEST => unsafe {
+                opus_rs_custom_decoder_set_phase_inversion_disabled(self.dec, val)
+            },
+            _ => return Err(Error::invalid(Operation::Ctl(key), "unsupported request")),
+        };
+
+        if ret < 0 {
+            Err(Error::opus(Operation::Ctl(key), ret))
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn get_option(&self, key: u32) -> Result<i32, Error> {
+        let dec = self.dec;
+        let mut val: i32 = 0;
+        let ret = unsafe {
+            let v = &mut val as *mut i32;
+            match key {
+                OPUS_GET_LOOKAHEAD_REQUEST => opus_rs_custom_decoder_get_lookahead(dec, v),
+                OPUS_GET_PITCH_REQUEST => opus_rs_custom_decoder_get_pitch(dec, v),
+                OPUS_GET_FINAL_RANGE_REQUEST => {
+                    opus_rs_custom_decoder_get_final_range(dec, v as *mut u32)
+                }
+                OPUS_GET_PHASE_INVERSION_DISABLED_REQUEST => {
+                    opus_rs_custom_decoder_get_phase_inversion_disabled(dec, v)
+                }
+                _ => return Err(Error::invalid(Operation::Ctl(key), "unsupported request")),
+            }
+        };
+
+        if ret < 0 {
+            Err(Error::opus(Operation::Ctl(key), ret))
+        } else {
+            Ok(val)
+        }
+    }
+
+    pub fn reset(&mut self) {
+        let _ = unsafe { opus_rs_custom_decoder_reset_state(self.dec) };
+    }
+}
+
+impl Drop for CustomDecoder<'_> {
+    fn drop(&mut self) {
+        unsafe { opus_custom_decoder_destroy(self.dec) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn custom_roundtrip() {
+        if !available() {
+            return;
+        }
+        assert!(CustomMode::create(48000, 7).is_err());
+
+        let mode = CustomMode::create(44100, 128).unwrap();
+        let mut enc = CustomEncoder::create(&mode, 2).unwrap();
+        let mut dec = CustomDecoder::create(&mode, 2).unwrap();
+        enc.set_option(OPUS_SET_BITRATE_REQUEST, 128000).unwrap();
+
+        let input: Vec<f32> = (0..128 * 2)
+            .map(|i| ((i / 2) as f32 * 0.05).sin() * 0.5)
+            .collect();
+        let mut output = vec![0.0f32; 128 * 2];
+        let mut packet = [0u8; 1275];
+
+        assert!(enc.encode(&input[..64], &mut packet[..]).is_err());
+
+        for _ in 0..4 {
+            let len = enc.encode(&input[..], &mut packet[..]).unwrap();
+            assert!(len > 0 && len < 200);
+            assert_eq!(dec.decode(&packet[..len], &mut output[..]).unwrap(), 128);
+        }
+        assert_eq!(
+            enc.get_option(OPUS_GET_FINAL_RANGE_REQUEST).unwrap(),
+            dec.get_option(OPUS_GET_FINAL_RANGE_REQUEST).unwrap()
+        );
+        assert!(output.iter().any(|&s| s != 0.0));
+
+        assert_eq!(dec.decode(None, &mut output[..]).unwrap(), 128);
+    }
+}
@@ -1,11 +1,28 @@
 use crate::common::*;
 use crate::ffi::*;
+use crate::planar::Scratch;
 
 use std::ptr;
+#[cfg(feature = "dred")]
+use std::sync::Arc;
+
+#[cfg(feature = "dred")]
+use crate::dred::Dred;
 
 pub struct Decoder {
     dec: *mut OpusMSDecoder,
+    sample_rate: usize,
     channels: usize,
+    streams: usize,
+    coupled_streams: usize,
+    mapping: Vec<u8>,
+    /// Memory the state lives in, if not allocated by libopus
+    mem: Option<StateMemory>,
+    /// Interleaved samples split into planar output
+    scratch: Scratch,
+    /// DNN weights the state points into, see `set_dnn_blob`
+    #[cfg(feature = "dred")]
+    dnn: Option<Arc<[u8]>>,
 }
 
 unsafe impl Send for Decoder {} // TODO: Make sure it cannot be abused
@@ -18,7 +35,13 @@ impl Decoder {
         streams: usize,
         coupled_streams: usize,
         mapping: &[u8],
-    ) -> Result<Decoder, ErrorCode> {
+    ) -> Result<Decoder, Error> {
+        loaded(Operation::Create)?;
+
+        if mapping.len() < channels {
+            return Err(Error::invalid(Operation::Create, "mapping too short"));
+        }
+
         let mut err = 0;
         let dec = unsafe {
             opus_multistream_decoder_create(
@@ -32,98 +55,596 @@ impl Decoder {
         };
 
         if err < 0 {
-            Err(err.into())
+            Err(Error::opus(Operation::Create, err))
         } else {
-            Ok(Decoder { dec, channels })
+            Ok(Decoder {
+                dec,
+                sample_rate,
+                channels,
+                streams,
+                coupled_streams,
+                mapping: mapping[..channels].to_vec(),
+                mem: None,
+                scratch: Scratch::default(),
+                #[cfg(feature = "dred")]
+                dnn: None,
+            })
+        }
+    }
+
+    /// Like `create`, initializing the state in `mem` instead of allocating.
+    ///
+    /// `mem` must be at least `Decoder::state_size(streams, coupled_streams)`
+    /// bytes long.
+    pub fn create_in(
+        mut mem: StateMemory,
+        sample_rate: usize,
+        channels: usize,
+        streams: usize,
+        coupled_streams: usize,
+        mapping: &[u8],
+    ) -> Result<Decoder, Error> {
+        loaded(Operation::Create)?;
+
+        let size = Decoder::state_size(streams, coupled_streams)?;
+        if mapping.len() < channels {
+            return Err(Error::invalid(Operation::Create, "mapping too short"));
+        }
+        if mem.len() < size {
+            return Err(Error::invalid(Operation::Create, "state memory too small"));
         }
+
+        let dec = mem.as_mut_ptr() as *mut OpusMSDecoder;
+        let err = unsafe {
+            opus_multistream_decoder_init(
+                dec,
+                sample_rate as i32,
+                channels as i32,
+                streams as i32,
+                coupled_streams as i32,
+                mapping.as_ptr(),
+            )
+        };
+
+        if err < 0 {
+            Err(Error::opus(Operation::Create, err))
+        } else {
+            Ok(Decoder {
+                dec,
+                sample_rate,
+                channels,
+                streams,
+                coupled_streams,
+                mapping: mapping[..channels].to_vec(),
+                mem: Some(mem),
+                scratch: Scratch::default(),
+                #[cfg(feature = "dred")]
+                dnn: None,
+            })
+        }
+    }
+
+    /// Bytes needed by the state of a decoder created with `create_in`.
+    pub fn state_size(streams: usize, coupled_streams: usize) -> Result<usize, Error> {
+        loaded(Operation::Create)?;
+
+        let size =
+            unsafe { opus_multistream_decoder_get_size(streams as i32, coupled_streams as i32) };
+        if size <= 0 {
+            return Err(Error::invalid(Operation::Create, "invalid stream layout"));
+        }
+
+        Ok(size as usize)
+    }
+
+    /// Gives back the memory of a decoder created with `create_in`.
+    pub fn into_memory(mut self) -> Option<StateMemory> {
+        let mem = self.mem.take();
+        if mem.is_some() {
+            self.dec = ptr::null_mut();
+        }
+        mem
+    }
+
+    /// Creates a decoder producing `output_channels` channels out of a
+    /// stream coded with the given mapping.
+    ///
+    /// libopus mixes a mono or stereo stream to the requested count. Out of
+    /// family 1 surround only the front channels are decoded, the others
+    /// are dropped rather than mixed in.
+    pub fn create_with_output_channels(
+        sample_rate: usize,
+        output_channels: usize,
+        mapping_family: u8,
+        streams: usize,
+        coupled_streams: usize,
+        mapping: &[u8],
+    ) -> Result<Decoder, Error> {
+        let (streams, coupled_streams, mapping) = select_channels(
+            output_channels,
+            mapping_family,
+            streams,
+            coupled_streams,
+            mapping,
+        )
+        .ok_or_else(|| Error::invalid(Operation::Create, "unsupported output channel count"))?;
+
+        Decoder::create(
+            sample_rate,
+            output_channels,
+            streams,
+            coupled_streams,
+            &mapping,
+        )
+    }
+
+    /// Size in bytes of the libopus state.
+    fn size(&self) -> usize {
+        let size = unsafe {
+            opus_multistream_decoder_get_size(self.streams as i32, self.coupled_streams as i32)
+        };
+
+        size as usize
+    }
+
+    pub fn sample_rate(&self) -> usize {
+        self.sample_rate
     }
 
-    pub fn decode<'a, I, O>(
+    pub fn channels(&self) -> usize {
+        self.channels
+    }
+
+    pub fn streams(&self) -> usize {
+        self.streams
+    }
+
+    pub fn coupled_streams(&self) -> usize {
+        self.coupled_streams
+    }
+
+    /// Stream channel each output channel is decoded from, 255 for silence.
+    pub fn mapping(&self) -> &[u8] {
+        &self.mapping
+    }
+
+    pub fn decode<'a, 'b, 'c: 'b, I, O>(
         &mut self,
         input: I,
         out: O,
         decode_fec: bool,
-    ) -> Result<usize, ErrorCode>
+    ) -> Result<usize, Error>
     where
         I: Into<Option<&'a [u8]>>,
-        O: Into<AudioBufferMut<'a>>,
+        O: Into<AudioBufferMut<'b, 'c>>,
     {
         let (data, len) = input
             .into()
             .map_or((ptr::null(), 0), |v| (v.as_ptr(), v.len()));
 
-        let ret = match out.into() {
-            AudioBufferMut::F32(v) => unsafe {
+        let (dec, channels) = (self.dec, self.channels);
+        let ret = out.into().interleaved(
+            &mut self.scratch,
+            channels,
+            Operation::Decode,
+            |v| unsafe {
                 opus_multistream_decode_float(
-                    self.dec,
+                    dec,
                     data,
                     len as i32,
                     v.as_mut_ptr(),
-                    (v.len() / self.channels) as i32,
+                    (v.len() / channels) as i32,
                     decode_fec as i32,
                 )
             },
-            AudioBufferMut::I16(v) => unsafe {
+            |v| unsafe {
                 opus_multistream_decode(
-                    self.dec,
+                    dec,
                     data,
                     len as i32,
                     v.as_mut_ptr(),
-                    (v.len() / self.channels) as i32,
+                    (v.len() / channels) as i32,
                     decode_fec as i32,
                 )
             },
-        };
+        )?;
 
         if ret < 0 {
-            Err(ret.into())
+            Err(Error::opus(Operation::Decode, ret))
         } else {
             Ok(ret as usize)
         }
     }
 
-    pub fn set_option(&mut self, key: u32, val: i32) -> Result<(), ErrorCode> {
+    pub fn set_option(&mut self, key: u32, val: i32) -> Result<(), Error> {
         let ret = match key {
-            OPUS_SET_GAIN_REQUEST => unsafe {
-                opus_multistream_decoder_ctl(self.dec, key as i32, val)
+            OPUS_SET_GAIN_REQUEST => unsafe { opus_rs_multistream_decoder_set_gain(self.dec, val) },
+            OPUS_SET_PHASE_INVERSION_DISABLED_REQUEST => unsafe {
+                opus_rs_multistream_decoder_set_phase_inversion_disabled(self.dec, val)
             },
-            _ => unimplemented!(),
+            // The multistream decoder does not forward it, set every stream
+            #[cfg(feature = "dred")]
+            OPUS_SET_COMPLEXITY_REQUEST => {
+                return self
+                    .each_stream(key, |st| unsafe { opus_rs_decoder_set_complexity(st, val) })
+            }
+            _ => return Err(Error::invalid(Operation::Ctl(key), "unsupported request")),
         };
 
         if ret < 0 {
-            Err(ret.into())
+            Err(Error::opus(Operation::Ctl(key), ret))
         } else {
             Ok(())
         }
     }
 
     pub fn reset(&mut self) {
-        let _ = unsafe { opus_multistream_decoder_ctl(self.dec, OPUS_RESET_STATE as i32) };
+        let _ = unsafe { opus_rs_multistream_decoder_reset_state(self.dec) };
+    }
+
+    /// Copies the whole decoder state, the copy continues decoding exactly
+    /// where the original is.
+    pub fn try_clone(&self) -> Result<Decoder, Error> {
+        // Allocate and initialize a state with the same layout, the settings
+        // are overwritten by the copy.
+        let dec = Decoder::create(
+            self.sample_rate,
+            self.channels,
+            self.streams,
+            self.coupled_streams,
+            &self.mapping,
+        )?;
+
+        unsafe {
+            ptr::copy_nonoverlapping(self.dec as *const u8, dec.dec as *mut u8, self.size());
+        }
+
+        // The copied state points into the same weights
+        #[cfg(feature = "dred")]
+        let dec = {
+            let mut dec = dec;
+            dec.dnn = self.dnn.clone();
+            dec
+        };
+
+        Ok(dec)
     }
 }
 
+/// Neural packet loss concealment and DRED, available from libopus 1.5.
+///
+/// The decoder complexity, set with `OPUS_SET_COMPLEXITY_REQUEST`, selects
+/// the neural tools: 5 or more enables the deep PLC, 6 and 7 also enhance
+/// the decoded speech with OSCE.
+#[cfg(feature = "dred")]
+impl Decoder {
+    /// Calls `f` on the state of every stream, stopping at the first error.
+    fn each_stream<F>(&mut self, key: u32, mut f: F) -> Result<(), Error>
+    where
+        F: FnMut(*mut OpusDecoder) -> i32,
+    {
+        for id in 0..self.streams {
+            let mut st = ptr::null_mut();
+            let ret = unsafe {
+                opus_rs_multistream_decoder_get_decoder_state(self.dec, id as i32, &mut st)
+            };
+            let ret = if ret < 0 { ret } else { f(st) };
+
+            if ret < 0 {
+                return Err(Error::opus(Operation::StreamCtl(key, id), ret));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Loads the DNN weights used by the deep PLC and OSCE.
+    /// The blob is kept alive with the state, see `DredDecoder::set_dnn_blob`.
+    pub fn set_dnn_blob(&mut self, blob: Arc<[u8]>) -> Result<(), Error> {
+        let key = OPUS_SET_DNN_BLOB_REQUEST;
+
+        if !supports(Capability::DeepPlc) {
+            return Err(Error::invalid(
+                Operation::Ctl(key),
+                "deep PLC needs libopus 1.5",
+            ));
+        }
+
+        let (data, len) = (blob.as_ptr() as *const _, blob.len() as i32);
+        self.each_stream(key, |st| unsafe {
+            opus_rs_decoder_set_dnn_blob(st, data, len)
+        })?;
+        self.dnn = Some(blob);
+
+        Ok(())
+    }
+
+    /// Rebuilds lost audio from the redundancy in `dred`, starting `offset`
+    /// samples before the packet it was parsed from and filling `out`.
+    ///
+    /// Only single stream decoders with the channels in stream order can
+    /// use DRED. `out` must hold a multiple of 2.5 ms.
+    pub fn decode_dred<'a, 'b: 'a, O>(
+        &mut self,
+        dred: &Dred,
+        offset: usize,
+        out: O,
+    ) -> Result<usize, Error>
+    where
+        O: Into<AudioBufferMut<'a, 'b>>,
+    {
+        let ordered = self
+            .mapping
+            .iter()
+            .enumerate()
+            .all(|(i, &m)| m as usize == i);
+        if self.streams != 1 || !ordered {
+            return Err(Error::invalid(
+                Operation::Decode,
+                "DRED needs a single stream",
+            ));
+        }
+
+        let mut st = ptr::null_mut();
+        let ret = unsafe { opus_rs_multistream_decoder_get_decoder_state(self.dec, 0, &mut st) };
+        if ret < 0 {
+            return Err(Error::opus(Operation::Decode, ret));
+        }
+
+        let channels = self.channels;
+        let ret = out.into().interleaved(
+            &mut self.scratch,
+            channels,
+            Operation::Decode,
+            |v| unsafe {
+                opus_decoder_dred_decode_float(
+                    st,
+                    dred.as_ptr(),
+                    offset as i32,
+                    v.as_mut_ptr(),
+                    (v.len() / channels) as i32,
+                )
+            },
+            |v| unsafe {
+                opus_decoder_dred_decode(
+                    st,
+                    dred.as_ptr(),
+                    offset as i32,
+                    v.as_mut_ptr(),
+                    (v.len() / channels) as i32,
+                )
+            },
+        )?;
+
+        if ret < 0 {
+            Err(Error::opus(Operation::Decode, ret))
+        } else {
+            Ok(ret as usize)
+        }
+    }
+}
+
+/// Computes the streams, coupled streams and mapping that decode a stream
+/// to `output_channels` channels.
+///
+/// A single mono or stereo stream is decoded by a decoder with the
+/// requested channel count, which mixes it down or up. Family 1 surround
+/// is decoded selecting channels, without mixing: left and right for
+/// stereo, center (or left if there is none) for mono.
+pub(crate) fn select_channels(
+    output_channels: usize,
+    mapping_family: u8,
+    streams: usize,
+    coupled_streams: usize,
+    mapping: &[u8],
+) -> Option<(usize, usize, Vec<u8>)> {
+    let channels = mapping.len();
+
+    if output_channels == channels {
+        return Some((streams, coupled_streams, mapping.to_vec()));
+    }
+
+    if streams == 1 && channels <= 2 {
+        return match output_channels {
+            1 => Some((1, 0, vec![0])),
+            2 => Some((1, 1, vec![0, 1])),
+            _ => None,
+        };
+    }
+
+    if mapping_family == 1 {
+        // Front channels positions in the Vorbis layouts
+        let (left, center, right) = match channels {
+            3 | 5..=8 => (0, 1, 2),
+            4 => (0, 0, 1),
+            _ => return None,
+        };
+        return match output_channels {
+            1 => Some((streams, coupled_streams, vec![mapping[center]])),
+            2 => Some((
+                streams,
+                coupled_streams,
+                vec![mapping[left], mapping[right]],
+            )),
+            _ => None,
+        };
+    }
+
+    None
+}
+
 impl Drop for Decoder {
     fn drop(&mut self) {
-        unsafe { opus_multistream_decoder_destroy(self.dec) }
+        // States living in a StateMemory are freed along with it
+        if self.mem.is_none() && !self.dec.is_null() {
+            unsafe { opus_multistream_decoder_destroy(self.dec) }
+        }
+    }
+}
+
+/// Layout of the decoders handed out by a `DecoderPool`.
+#[derive(Clone, Debug)]
+pub struct DecoderLayout {
+    pub sample_rate: usize,
+    pub channels: usize,
+    pub streams: usize,
+    pub coupled_streams: usize,
+    pub mapping: Vec<u8>,
+}
+
+/// Pool of pre-initialized decoders sharing the same layout.
+///
+/// Decoders returned to the pool are initialized again in place, so `get`
+/// hands out decoders in their default state without allocating as long
+/// as the pool is not empty.
+pub struct DecoderPool {
+    layout: DecoderLayout,
+    free: Vec<Decoder>,
+}
+
+impl DecoderPool {
+    /// Creates a pool holding `count` ready decoders.
+    pub fn new(layout: DecoderLayout, count: usize) -> Result<DecoderPool, Error> {
+        let mut pool = DecoderPool {
+            layout,
+            free: Vec::with_capacity(count),
+        };
+
+        for _ in 0..count {
+            let dec = pool.create()?;
+            pool.free.push(dec);
+        }
+
+        Ok(pool)
+    }
+
+    /// Creates a pool with a decoder in each of `memory`, e.g. caller
+    /// buffers wrapped by `StateMemory::from_boxed`.
+    ///
+    /// Once they are all handed out, `get` allocates new ones.
+    pub fn with_memory<I>(layout: DecoderLayout, memory: I) -> Result<DecoderPool, Error>
+    where
+        I: IntoIterator<Item = StateMemory>,
+    {
+        let memory = memory.into_iter();
+        let mut pool = DecoderPool {
+            layout,
+            free: Vec::with_capacity(memory.size_hint().0),
+        };
+
+        for mem in memory {
+            let dec = pool.create_in(mem)?;
+            pool.free.push(dec);
+        }
+
+        Ok(pool)
+    }
+
+    fn create(&self) -> Result<Decoder, Error> {
+        let l = &self.layout;
+        let mem = StateMemory::new(Decoder::state_size(l.streams, l.coupled_streams)?);
+
+        self.create_in(mem)
+    }
+
+    fn create_in(&self, mem: StateMemory) -> Result<Decoder, Error> {
+        let l = &self.layout;
+
+        Decoder::create_in(
+            mem,
+            l.sample_rate,
+            l.channels,
+            l.streams,
+            l.coupled_streams,
+            &l.mapping,
+        )
+    }
+
+    pub fn layout(&self) -> &DecoderLayout {
+        &self.layout
+    }
+
+    /// Number of decoders ready to be handed out.
+    pub fn available(&self) -> usize {
+        self.free.len()
+    }
+
+    /// Takes a decoder from the pool, creating one if the pool is empty.
+    pub fn get(&mut self) -> Result<Decoder, Error> {
+        match self.free.pop() {
+            Some(dec) => Ok(dec),
+            None => self.create(),
+        }
+    }
+
+    /// Returns a decoder to the pool, decoders with a different layout are
+    /// dropped.
+    pub fn put(&mut self, dec: Decoder) {
+        let l = &self.layout;
+        if dec.channels != l.channels
+            || dec.streams != l.streams
+            || dec.coupled_streams != l.coupled_streams
+            || dec.mapping != l.mapping
+        {
+            return;
+        }
+
+        if let Some(mem) = dec.into_memory() {
+            if let Ok(dec) = self.create_in(mem) {
+                self.free.push(dec);
+            }
+        }
     }
 }
 
 #[cfg(feature = "codec-trait")]
 mod decoder_trait {
+    use super::select_channels;
     use super::Decoder as OpusDecoder;
-    use bitstream::byteread::get_i16l;
+    use crate::common::channel_map::{discrete_map, vorbis_map};
+    use bitstream::byteread::{get_i16l, get_u16l};
     use codec::decoder::*;
     use codec::error::*;
-    use data::audiosample::formats::S16;
-    use data::audiosample::ChannelMap;
+    use data::audiosample::formats::{F32, S16};
+    use data::audiosample::{ChannelMap, Soniton};
     use data::frame::*;
     use data::packet::Packet;
+    use data::rational::Rational64;
+    use data::timeinfo::TimeInfo;
+    use data::value::Value;
     use ffi::OPUS_SET_GAIN_REQUEST;
     use std::collections::VecDeque;
     use std::sync::Arc;
 
+    #[cfg(feature = "dred")]
+    use crate::dred::{Dred, DredDecoder, DredRange};
+    #[cfg(feature = "dred")]
+    use ffi::OPUS_SET_COMPLEXITY_REQUEST;
+
+    /// Marker stored in `TimeInfo::user_private` of the frames synthesized
+    /// by packet-loss concealment.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Concealed;
+
+    /// Returns true if the frame was synthesized to conceal lost or
+    /// corrupt packets.
+    pub fn is_concealed(frame: &Frame) -> bool {
+        match frame.t.user_private {
+            Some(ref p) => p.is::<Concealed>(),
+            None => false,
+        }
+    }
+
     pub struct Des {
         descr: Descr,
+        formats: &'static [Soniton],
+    }
+
+    impl Des {
+        /// Sample formats the decoder can output.
+        pub fn formats(&self) -> &[Soniton] {
+            self.formats
+        }
     }
 
     pub struct Dec {
@@ -131,6 +652,30 @@ mod decoder_trait {
         extradata: Option<Vec<u8>>,
         pending: VecDeque<ArcFrame>,
         info: AudioInfo,
+        /// Priming samples at 48kHz, as stored in OpusHead
+        pre_skip: usize,
+        /// Priming samples still to be discarded at the output rate
+        skip: usize,
+        /// Conceal lost and corrupt packets instead of failing
+        plc: bool,
+        /// Recover the last lost frame from the FEC data of the next packet
+        fec: bool,
+        /// Timestamp the next packet is expected at
+        next_pts: Option<i64>,
+        /// Samples decoded from the last packet, used as loss duration
+        last_samples: usize,
+        /// Channel count to decode to instead of the coded one
+        output_channels: Option<usize>,
+        /// Timestamp of the first sample of the current output run
+        timeline: Option<(i64, Rational64)>,
+        /// Samples output since the start of the run
+        position: usize,
+        /// Decoder complexity, 5 and above enable the neural PLC
+        #[cfg(feature = "dred")]
+        complexity: Option<i32>,
+        /// Rebuild lost audio from the DRED of the next packet
+        #[cfg(feature = "dred")]
+        dred: Option<(DredDecoder, Dred)>,
     }
 
     impl Dec {
@@ -146,7 +691,306 @@ mod decoder_trait {
                     format: Arc::new(S16),
                     block_len: None,
                 },
+                pre_skip: 0,
+                skip: 0,
+                plc: true,
+                fec: false,
+                next_pts: None,
+                last_samples: 0,
+                output_channels: None,
+                timeline: None,
+                position: 0,
+                #[cfg(feature = "dred")]
+                complexity: None,
+                #[cfg(feature = "dred")]
+                dred: None,
+            }
+        }
+
+        /// Decodes `data` into a new frame, `None` conceals `samples` lost
+        /// samples. Returns the frame and the decoded sample count.
+        fn decode_frame(
+            &mut self,
+            data: Option<&[u8]>,
+            samples: usize,
+            fec: bool,
+            t: TimeInfo,
+        ) -> Result<(Frame, usize)> {
+            let mut f = Frame::new_default_frame(self.info.clone(), Some(t));
+            let len = samples * self.info.map.len();
+            let dec = self.dec.as_mut().ok_or(Error::ConfigurationIncomplete)?;
+
+            let ret = if *self.info.format == F32 {
+                let buf: &mut [f32] = f.buf.as_mut_slice(0).unwrap();
+                dec.decode(data, &mut buf[..len], fec)
+            } else {
+                let buf: &mut [i16] = f.buf.as_mut_slice(0).unwrap();
+                dec.decode(data, &mut buf[..len], fec)
+            };
+
+            Ok((f, ret?))
+        }
+
+        /// Synthesizes `samples` samples starting at `pts`, the count is
+        /// rounded down to the 2.5ms granularity libopus conceals in.
+        fn conceal(
+            &mut self,
+            samples: usize,
+            pts: Option<i64>,
+            timebase: Option<Rational64>,
+        ) -> Result<()> {
+            let step = self.info.sample_rate / 400;
+            let mut remaining = samples - samples % step;
+            let mut pts = pts;
+
+            while remaining > 0 {
+                let samples = remaining.min(self.info.samples);
+                let t = TimeInfo {
+                    pts,
+                    timebase,
+                    user_private: Some(Arc::new(Concealed)),
+                    ..Default::default()
+                };
+                let (f, n) = self.decode_frame(None, samples, false, t)?;
+                self.output(f, n, pts, timebase);
+                pts = match (pts, timebase) {
+                    (Some(pts), Some(tb)) => Some(pts + self.samples_to_ts(n, tb)),
+                    _ => None,
+                };
+                remaining -= samples;
+            }
+
+            Ok(())
+        }
+
+        /// Synthesizes the first `samples` samples of a gap of `lost`
+        /// samples ending where `pkt` starts.
+        #[cfg(not(feature = "dred"))]
+        fn recover(
+            &mut self,
+            _pkt: &Packet,
+            _lost: usize,
+            samples: usize,
+            timebase: Option<Rational64>,
+        ) -> Result<()> {
+            self.conceal(samples, self.next_pts, timebase)
+        }
+
+        /// Synthesizes the first `samples` samples of a gap of `lost`
+        /// samples ending where `pkt` starts, rebuilding the part covered
+        /// by the DRED of `pkt` and concealing the rest.
+        #[cfg(feature = "dred")]
+        fn recover(
+            &mut self,
+            pkt: &Packet,
+            lost: usize,
+            samples: usize,
+            timebase: Option<Rational64>,
+        ) -> Result<()> {
+            let step = self.info.sample_rate / 400;
+            let range = match self.dred {
+                Some((ref mut dred_dec, ref mut dred)) if !pkt.is_corrupted => dred_dec
+                    .parse(dred, &pkt.data, lost, self.info.sample_rate)
+                    .unwrap_or_default(),
+                _ => DredRange::default(),
+            };
+
+            // Positions in the gap, the DRED offsets count back from its end
+            let start = lost.saturating_sub(range.offset).div_ceil(step) * step;
+            let end = lost.saturating_sub(range.end).min(samples);
+            if range.is_empty() || start >= end {
+                return self.conceal(samples, self.next_pts, timebase);
+            }
+            let end = end - (end - start) % step;
+
+            self.conceal(start, self.next_pts, timebase)?;
+
+            let mut pos = start;
+            while pos < end {
+                let n = (end - pos).min(self.info.samples);
+                let pts = match (self.next_pts, timebase) {
+                    (Some(pts), Some(tb)) => Some(pts + self.samples_to_ts(pos, tb)),
+                    _ => None,
+                };
+                let t = TimeInfo {
+                    pts,
+                    timebase,
+                    user_private: Some(Arc::new(Concealed)),
+                    ..Default::default()
+                };
+                let mut f = Frame::new_default_frame(self.info.clone(), Some(t));
+                let len = n * self.info.map.len();
+                let dec = self.dec.as_mut().ok_or(Error::ConfigurationIncomplete)?;
+                let dred = &self.dred.as_ref().unwrap().1;
+
+                let ret = if *self.info.format == F32 {
+                    let buf: &mut [f32] = f.buf.as_mut_slice(0).unwrap();
+                    dec.decode_dred(dred, lost - pos, &mut buf[..len])
+                } else {
+                    let buf: &mut [i16] = f.buf.as_mut_slice(0).unwrap();
+                    dec.decode_dred(dred, lost - pos, &mut buf[..len])
+                };
+
+                match ret {
+                    Ok(n) => self.output(f, n, pts, timebase),
+                    Err(_) => self.conceal(n, pts, timebase)?,
+                }
+                pos += n;
+            }
+
+            let pts = match (self.next_pts, timebase) {
+                (Some(pts), Some(tb)) => Some(pts + self.samples_to_ts(end, tb)),
+                _ => None,
+            };
+            self.conceal(samples - end, pts, timebase)
+        }
+
+        /// Drops the pre-skip from the first `samples` of the frame and
+        /// queues what is left.
+        fn output(
+            &mut self,
+            mut f: Frame,
+            samples: usize,
+            pts: Option<i64>,
+            timebase: Option<Rational64>,
+        ) {
+            let channels = self.info.map.len();
+            let skip = self.skip.min(samples);
+            let samples = samples - skip;
+            self.skip -= skip;
+
+            if samples == 0 {
+                return;
+            }
+
+            if skip > 0 {
+                let range = skip * channels..(skip + samples) * channels;
+                if *self.info.format == F32 {
+                    let buf: &mut [f32] = f.buf.as_mut_slice(0).unwrap();
+                    buf.copy_within(range, 0);
+                } else {
+                    let buf: &mut [i16] = f.buf.as_mut_slice(0).unwrap();
+                    buf.copy_within(range, 0);
+                }
             }
+
+            let timebase = timebase
+                .or_else(|| self.timeline.map(|t| t.1))
+                .unwrap_or_else(|| Rational64::new(1, self.info.sample_rate as i64));
+
+            // The packet timestamps count the priming samples, the
+            // output timeline starts with the first real sample.
+            let pre_skip = self.pre_skip * self.info.sample_rate / 48000;
+            let offset =
+                Rational64::new(skip as i64 - pre_skip as i64, self.info.sample_rate as i64)
+                    / timebase;
+            let pts = pts.map(|pts| (offset + pts).round().to_integer());
+
+            // Timestamps are derived from the samples output since the
+            // start of the run so they never drift, a packet off by more
+            // than the rounding error starts a new run.
+            let expected = match self.timeline {
+                Some((origin, tb)) if tb == timebase => {
+                    Some(origin + self.samples_to_ts(self.position, tb))
+                }
+                _ => None,
+            };
+            match (pts, expected) {
+                (Some(pts), Some(expected)) if (pts - expected).abs() <= 1 => {}
+                (None, Some(_)) => {}
+                (pts, _) => {
+                    self.timeline = Some((pts.unwrap_or(0), timebase));
+                    self.position = 0;
+                }
+            }
+
+            let origin = self.timeline.map_or(0, |t| t.0);
+            let start = self.samples_to_ts(self.position, timebase);
+            let end = self.samples_to_ts(self.position + samples, timebase);
+            f.t.pts = Some(origin + start);
+            f.t.duration = Some((end - start) as u64);
+            f.t.timebase = Some(timebase);
+            self.position += samples;
+
+            if let MediaKind::Audio(ref mut info) = f.kind {
+                info.samples = samples;
+            }
+            self.pending.push_back(Arc::new(f));
+        }
+
+        fn reset_skip(&mut self) {
+            self.skip = self.pre_skip * self.info.sample_rate / 48000;
+        }
+
+        /// Converts a sample count at the output rate into `timebase` units.
+        fn samples_to_ts(&self, samples: usize, timebase: Rational64) -> i64 {
+            (Rational64::new(samples as i64, self.info.sample_rate as i64) / timebase).to_integer()
+        }
+
+        /// Converts a packet duration into a sample count at the output rate.
+        fn ts_to_samples(&self, ts: i64, timebase: Rational64) -> usize {
+            (Rational64::from_integer(ts) * timebase * self.info.sample_rate as i64)
+                .round()
+                .to_integer()
+                .max(0) as usize
+        }
+
+        /// Sets a decoder option, `format` selects the output sample format
+        /// between `s16` and `f32`, `sample_rate` the output rate, `plc`
+        /// toggles the concealment of lost packets and `fec` the use of
+        /// the forward error correction data to recover them and
+        /// `output_channels` the channel count to decode to.
+        pub fn set_option<'a>(&mut self, key: &str, val: Value<'a>) -> Result<()> {
+            match (key, val) {
+                ("sample_rate", Value::U64(v)) => {
+                    let rate = v as usize;
+                    match rate {
+                        8000 | 12000 | 16000 | 24000 | 48000 => {}
+                        _ => return Err(Error::Unsupported("Unsupported sample rate".to_owned())),
+                    }
+                    if self.dec.is_some() && rate != self.info.sample_rate {
+                        return Err(Error::ConfigurationInvalid);
+                    }
+                    self.info.sample_rate = rate;
+                    // Room for the longest (120ms) packet
+                    self.info.samples = rate * 120 / 1000;
+                }
+                ("format", Value::Str("s16")) => self.info.format = Arc::new(S16),
+                ("format", Value::Str("f32")) => self.info.format = Arc::new(F32),
+                ("format", _) => return Err(Error::InvalidData),
+                ("plc", Value::Bool(v)) => self.plc = v,
+                ("output_channels", Value::U64(v)) => {
+                    if self.dec.is_some() {
+                        return Err(Error::ConfigurationInvalid);
+                    }
+                    if v == 0 || v > 255 {
+                        return Err(Error::InvalidData);
+                    }
+                    self.output_channels = Some(v as usize);
+                }
+                ("fec", Value::Bool(v)) => self.fec = v,
+                #[cfg(feature = "dred")]
+                ("complexity", Value::U64(v)) => {
+                    if v > 10 {
+                        return Err(Error::InvalidData);
+                    }
+                    if let Some(ref mut dec) = self.dec {
+                        dec.set_option(OPUS_SET_COMPLEXITY_REQUEST, v as i32)?;
+                    }
+                    self.complexity = Some(v as i32);
+                }
+                #[cfg(feature = "dred")]
+                ("dred", Value::Bool(v)) => {
+                    self.dred = if v {
+                        Some((DredDecoder::create()?, Dred::create()?))
+                    } else {
+                        None
+                    };
+                }
+                _ => return Err(Error::Unsupported("Unsupported option".to_owned())),
+            }
+
+            Ok(())
         }
     }
 
@@ -164,62 +1008,157 @@ mod decoder_trait {
 
     const OPUS_HEAD_SIZE: usize = 19;
 
+    /// Longest gap in the timestamps concealed by `send_packet`.
+    const MAX_CONCEALED_MS: usize = 120;
+
     impl Decoder for Dec {
         fn set_extradata(&mut self, extra: &[u8]) {
             self.extradata = Some(Vec::from(extra));
         }
         fn send_packet(&mut self, pkt: &Packet) -> Result<()> {
-            let mut f = Frame::new_default_frame(self.info.clone(), Some(pkt.t.clone()));
+            if self.dec.is_none() {
+                return Err(Error::ConfigurationIncomplete);
+            }
 
-            let ret = {
-                let buf: &mut [i16] = f.buf.as_mut_slice(0).unwrap();
+            let timebase = pkt.t.timebase;
+            let max_samples = self.info.samples;
 
-                self.dec
-                    .as_mut()
-                    .unwrap()
-                    .decode(pkt.data.as_slice(), buf, false)
-                    .map_err(|_e| Error::InvalidData)
-            };
+            // A jump past the expected timestamp means packets were lost
+            let mut lost = 0;
+            if let (Some(pts), Some(next), Some(tb)) = (pkt.t.pts, self.next_pts, timebase) {
+                if pts > next {
+                    lost = self.ts_to_samples(pts - next, tb);
+                }
+            }
+
+            // Past the longest packet the gap is a discontinuity, not a
+            // loss worth concealing: start over from this packet.
+            if lost > self.info.sample_rate * MAX_CONCEALED_MS / 1000 {
+                lost = 0;
+                if let Some(ref mut dec) = self.dec {
+                    dec.reset();
+                }
+            }
+
+            if lost > 0 && self.plc {
+                // The last lost frame can be rebuilt from the in-band FEC
+                let fec = if self.fec && !pkt.is_corrupted {
+                    self.last_samples.min(lost)
+                } else {
+                    0
+                };
+                let fec = fec - fec % (self.info.sample_rate / 400);
+
+                self.recover(pkt, lost, lost - fec, timebase)?;
 
-            match ret {
-                Ok(samples) => {
-                    if let MediaKind::Audio(ref mut info) = f.kind {
-                        info.samples = samples;
+                if fec > 0 {
+                    let pts = match (pkt.t.pts, timebase) {
+                        (Some(pts), Some(tb)) => Some(pts - self.samples_to_ts(fec, tb)),
+                        _ => None,
+                    };
+                    let t = TimeInfo {
+                        pts,
+                        timebase,
+                        user_private: Some(Arc::new(Concealed)),
+                        ..Default::default()
+                    };
+                    match self.decode_frame(Some(pkt.data.as_slice()), fec, true, t) {
+                        Ok((f, n)) => self.output(f, n, pts, timebase),
+                        Err(_) => self.conceal(fec, pts, timebase)?,
                     }
-                    self.pending.push_back(Arc::new(f));
-                    Ok(())
                 }
-                Err(e) => Err(e),
             }
+
+            let ret = if pkt.is_corrupted {
+                Err(Error::InvalidData)
+            } else {
+                self.decode_frame(Some(pkt.data.as_slice()), max_samples, false, pkt.t.clone())
+            };
+
+            let samples = match ret {
+                Ok((f, samples)) => {
+                    // A duration shorter than the decoded packet marks the
+                    // end of the stream, the padding past it is dropped.
+                    // Durations only rounded by a coarse timebase are not.
+                    let trimmed = match (pkt.t.duration, timebase) {
+                        (Some(duration), Some(tb))
+                            if self.samples_to_ts(samples, tb) > duration as i64 =>
+                        {
+                            samples.min(self.ts_to_samples(duration as i64, tb))
+                        }
+                        _ => samples,
+                    };
+                    self.output(f, trimmed, pkt.t.pts, timebase);
+                    samples
+                }
+                Err(e) => {
+                    if !self.plc {
+                        return Err(e);
+                    }
+                    // Conceal as much audio as the packet was meant to carry
+                    let samples = match (pkt.t.duration, timebase) {
+                        (Some(duration), Some(tb)) => self.ts_to_samples(duration as i64, tb),
+                        _ if self.last_samples > 0 => self.last_samples,
+                        _ => self.info.sample_rate / 50,
+                    };
+                    self.conceal(samples, pkt.t.pts, timebase)?;
+                    samples
+                }
+            };
+
+            self.last_samples = samples;
+            self.next_pts = match (pkt.t.pts, timebase) {
+                (Some(pts), Some(tb)) => Some(pts + self.samples_to_ts(samples, tb)),
+                _ => None,
+            };
+
+            Ok(())
         }
         fn receive_frame(&mut self) -> Result<ArcFrame> {
             self.pending.pop_front().ok_or(Error::MoreDataNeeded)
         }
         fn configure(&mut self) -> Result<()> {
             let channels;
-            let sample_rate = 48000;
+            let sample_rate = self.info.sample_rate;
+            let mut pre_skip = 0;
             let mut gain_db = 0;
             let mut streams = 1;
             let mut coupled_streams = 0;
             let mut mapping: &[u8] = &[0u8, 1u8];
-            let mut channel_map = false;
+            let mut mapping_family = 0;
 
             if let Some(ref extradata) = self.extradata {
                 channels = *extradata.get(9).unwrap_or(&2) as usize;
 
+                if channels == 0 {
+                    return Err(Error::ConfigurationInvalid);
+                }
+
                 if extradata.len() >= OPUS_HEAD_SIZE {
+                    pre_skip = get_u16l(&extradata[10..12]) as usize;
                     gain_db = get_i16l(&extradata[16..18]);
-                    channel_map = extradata[18] != 0;
+                    mapping_family = extradata[18];
                 }
-                if extradata.len() >= OPUS_HEAD_SIZE + 2 + channels {
+                if mapping_family != 0 {
+                    if extradata.len() < OPUS_HEAD_SIZE + 2 + channels {
+                        return Err(Error::ConfigurationInvalid);
+                    }
                     streams = extradata[OPUS_HEAD_SIZE] as usize;
                     coupled_streams = extradata[OPUS_HEAD_SIZE + 1] as usize;
-                    if streams + coupled_streams != channels {
-                        unimplemented!()
+                    mapping = &extradata[OPUS_HEAD_SIZE + 2..OPUS_HEAD_SIZE + 2 + channels];
+
+                    let coded_channels = streams + coupled_streams;
+                    if streams == 0
+                        || coupled_streams > streams
+                        || coded_channels > 255
+                        || mapping
+                            .iter()
+                            .any(|&m| m != 255 && m as usize >= coded_channels)
+                    {
+                        return Err(Error::ConfigurationInvalid);
                     }
-                    mapping = &extradata[OPUS_HEAD_SIZE + 2..]
                 } else {
-                    if channels > 2 || channel_map {
+                    if channels > 2 {
                         return Err(Error::ConfigurationInvalid);
                     }
                     if channels > 1 {
@@ -230,16 +1169,34 @@ mod decoder_trait {
                 return Err(Error::ConfigurationIncomplete);
             }
 
-            if channels > 2 {
-                unimplemented!() // TODO: Support properly channel mapping
-            } else {
-                self.info.map = ChannelMap::default_map(channels);
+            // Family 3 requires the projection decoder
+            if mapping_family == 3 {
+                return Err(Error::Unsupported("Ambisonics projection".to_owned()));
             }
 
-            match OpusDecoder::create(sample_rate, channels, streams, coupled_streams, mapping) {
+            let mapping = &mapping[..channels];
+            let channels = self.output_channels.unwrap_or(channels);
+            let (streams, coupled_streams, mapping) =
+                select_channels(channels, mapping_family, streams, coupled_streams, mapping)
+                    .ok_or_else(|| Error::Unsupported("Output channel count".to_owned()))?;
+
+            // Only mono, stereo and the Vorbis layouts define positions
+            self.info.map = match mapping_family {
+                0 | 1 => vorbis_map(channels)
+                    .ok_or_else(|| Error::Unsupported("More than 8 channels".to_owned()))?,
+                _ => discrete_map(channels),
+            };
+
+            match OpusDecoder::create(sample_rate, channels, streams, coupled_streams, &mapping) {
                 Ok(mut d) => {
                     let _ = d.set_option(OPUS_SET_GAIN_REQUEST, gain_db as i32);
+                    #[cfg(feature = "dred")]
+                    if let Some(complexity) = self.complexity {
+                        d.set_option(OPUS_SET_COMPLEXITY_REQUEST, complexity)?;
+                    }
                     self.dec = Some(d);
+                    self.pre_skip = pre_skip;
+                    self.reset_skip();
                     Ok(())
                 }
                 Err(_) => Err(Error::ConfigurationInvalid),
@@ -247,7 +1204,14 @@ mod decoder_trait {
         }
 
         fn flush(&mut self) -> Result<()> {
-            self.dec.as_mut().unwrap().reset();
+            self.dec
+                .as_mut()
+                .ok_or(Error::ConfigurationIncomplete)?
+                .reset();
+            self.reset_skip();
+            self.next_pts = None;
+            self.timeline = None;
+            self.position = 0;
             Ok(())
         }
     }
@@ -259,8 +1223,422 @@ mod decoder_trait {
             desc: "libopus decoder",
             mime: "audio/OPUS",
         },
+        formats: &[S16, F32],
     };
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::common::available;
+        use crate::encoder::trait_tests::{encode, stereo_encoder, stereo_frame};
+        use codec::encoder::{Descriptor as _, Encoder as _};
+        use data::audiosample::ChannelType;
+        use std::iter;
+
+        /// OpusHead for `channels` uncoupled streams in the given family.
+        fn opus_head(channels: u8, mapping_family: u8) -> Vec<u8> {
+            let mut head = b"OpusHead".to_vec();
+            head.extend_from_slice(&[1, channels, 0, 0]);
+            head.extend_from_slice(&48000u32.to_le_bytes());
+            head.extend_from_slice(&[0, 0, mapping_family, channels, 0]);
+            head.extend(0..channels);
+            head
+        }
+
+        /// Decodes `packets`, collecting the frames as they come out.
+        fn decode(dec: &mut Dec, packets: &[Packet]) -> Vec<ArcFrame> {
+            let mut frames = Vec::new();
+            for pkt in packets {
+                dec.send_packet(pkt).unwrap();
+                while let Ok(frame) = dec.receive_frame() {
+                    frames.push(frame);
+                }
+            }
+            frames
+        }
+
+        fn samples(frame: &Frame) -> usize {
+            match frame.kind {
+                MediaKind::Audio(ref info) => info.samples,
+                _ => unreachable!(),
+            }
+        }
+
+        #[test]
+        fn discrete_channels() {
+            if !available() {
+                return;
+            }
+            let mut dec = OPUS_DESCR.create();
+            dec.set_extradata(&opus_head(12, 255));
+            dec.configure().unwrap();
+            assert_eq!(dec.info.map.len(), 12);
+            assert_eq!(dec.info.map.get_channel(1), ChannelType::C);
+
+            // Four channels are not quadraphonic outside of family 1
+            dec.set_extradata(&opus_head(4, 255));
+            dec.configure().unwrap();
+            assert_eq!(dec.info.map.get_channel(0), ChannelType::C);
+
+            dec.set_extradata(&opus_head(4, 1));
+            dec.configure().unwrap();
+            assert_eq!(dec.info.map.get_channel(0), ChannelType::L);
+
+            dec.set_extradata(&opus_head(12, 1));
+            assert!(dec.configure().is_err());
+        }
+
+        #[test]
+        fn gapless_roundtrip() {
+            if !available() {
+                return;
+            }
+            let mut enc = stereo_encoder();
+            let mut packets = encode(&mut enc, &stereo_frame(1000, 0));
+            enc.flush().unwrap();
+            packets.extend(iter::from_fn(|| enc.receive_packet().ok()));
+
+            let mut dec = OPUS_DESCR.create();
+            dec.set_extradata(&enc.get_extradata().unwrap());
+            dec.configure().unwrap();
+
+            let mut decoded = 0;
+            for frame in decode(&mut dec, &packets) {
+                assert_eq!(frame.t.pts, Some(decoded as i64));
+                decoded += samples(&frame);
+            }
+
+            // Priming and padding are trimmed away
+            assert_eq!(decoded, 1000);
+        }
+
+        #[test]
+        fn conceal_lost_packets() {
+            if !available() {
+                return;
+            }
+            let mut enc = stereo_encoder();
+            let mut packets = encode(&mut enc, &stereo_frame(960 * 5, 0));
+            let delay = enc.get_params().unwrap().delay;
+            packets.remove(1);
+            packets[2].is_corrupted = true;
+
+            let mut dec = OPUS_DESCR.create();
+            dec.set_option("fec", Value::Bool(true)).unwrap();
+            dec.set_extradata(&enc.get_extradata().unwrap());
+            dec.configure().unwrap();
+
+            let mut decoded = 0;
+            let mut concealed = 0;
+            for frame in decode(&mut dec, &packets) {
+                assert_eq!(frame.t.pts, Some(decoded as i64));
+                decoded += samples(&frame);
+                if is_concealed(&frame) {
+                    concealed += 1;
+                }
+            }
+
+            assert_eq!(decoded, 960 * 5 - delay);
+            assert_eq!(concealed, 2);
+        }
+
+        #[test]
+        fn skip_long_gaps() {
+            if !available() {
+                return;
+            }
+            let mut enc = stereo_encoder();
+            let mut packets = encode(&mut enc, &stereo_frame(960 * 2, 0));
+            let delay = enc.get_params().unwrap().delay;
+
+            let mut dec = OPUS_DESCR.create();
+            dec.set_extradata(&enc.get_extradata().unwrap());
+            dec.configure().unwrap();
+
+            // A second long hole is not filled with concealed audio
+            packets[1].t.pts = packets[1].t.pts.map(|pts| pts + 48000);
+
+            let frames = decode(&mut dec, &packets);
+            assert!(frames.iter().all(|frame| !is_concealed(frame)));
+            let pts: Vec<i64> = frames.iter().map(|frame| frame.t.pts.unwrap()).collect();
+            assert_eq!(pts, vec![0, 48000 + 960 - delay as i64]);
+        }
+
+        #[cfg(feature = "dred")]
+        #[test]
+        fn dred_recovery() {
+            if !available() {
+                return;
+            }
+            let mut enc = stereo_encoder();
+            enc.set_option("packet_loss", Value::U64(20)).unwrap();
+            enc.set_option("dred_duration", Value::U64(50)).unwrap();
+            let mut packets = encode(&mut enc, &stereo_frame(960 * 10, 0));
+            let delay = enc.get_params().unwrap().delay;
+            packets.drain(4..7);
+
+            let mut dec = OPUS_DESCR.create();
+            dec.set_option("complexity", Value::U64(5)).unwrap();
+            dec.set_option("dred", Value::Bool(true)).unwrap();
+            dec.set_extradata(&enc.get_extradata().unwrap());
+            dec.configure().unwrap();
+
+            let mut decoded = 0;
+            let mut concealed = 0;
+            for frame in decode(&mut dec, &packets) {
+                assert_eq!(frame.t.pts, Some(decoded as i64));
+                decoded += samples(&frame);
+                if is_concealed(&frame) {
+                    concealed += 1;
+                }
+            }
+
+            assert_eq!(decoded, 960 * 10 - delay);
+            assert!(concealed > 0);
+        }
+
+        #[test]
+        fn decoded_timestamps() {
+            if !available() {
+                return;
+            }
+            let mut enc = stereo_encoder();
+            let mut packets = Vec::new();
+            let mut pts = 0;
+            for &(duration, samples) in &[("2.5", 480), ("20", 960), ("60", 2880)] {
+                enc.set_option("frame_duration", Value::Str(duration))
+                    .unwrap();
+                packets.extend(encode(&mut enc, &stereo_frame(samples, pts)));
+                pts += samples as i64;
+            }
+            let delay = enc.get_params().unwrap().delay;
+
+            // Millisecond timestamps cannot represent 2.5ms exactly
+            let ms = Rational64::new(1, 1000);
+            let to_ms = |v: i64| (Rational64::new(v, 48000) / ms).to_integer();
+            for pkt in &mut packets {
+                pkt.t.pts = pkt.t.pts.map(to_ms);
+                pkt.t.duration = pkt.t.duration.map(|d| to_ms(d as i64) as u64);
+                pkt.t.timebase = Some(ms);
+            }
+
+            let mut dec = OPUS_DESCR.create();
+            dec.set_extradata(&enc.get_extradata().unwrap());
+            dec.configure().unwrap();
+
+            fn timestamps(frames: &[ArcFrame]) -> Vec<(i64, u64)> {
+                let mut timestamps = Vec::new();
+                let mut end = 0;
+                for frame in frames {
+                    assert_eq!(frame.t.pts, Some(end));
+                    let duration = frame.t.duration.unwrap();
+                    timestamps.push((end, duration));
+                    end += duration as i64;
+                }
+                timestamps
+            }
+
+            let first = timestamps(&decode(&mut dec, &packets));
+            let (pts, duration) = first[first.len() - 1];
+            assert_eq!(pts + duration as i64, (4320 - delay) as i64 * 1000 / 48000);
+
+            dec.flush().unwrap();
+            assert_eq!(timestamps(&decode(&mut dec, &packets)), first);
+        }
+
+        #[test]
+        fn mono_downmix() {
+            if !available() {
+                return;
+            }
+            let mut enc = stereo_encoder();
+            let pkt = encode(&mut enc, &stereo_frame(960, 0)).remove(0);
+            let delay = enc.get_params().unwrap().delay;
+
+            let mut dec = OPUS_DESCR.create();
+            dec.set_option("output_channels", Value::U64(1)).unwrap();
+            dec.set_extradata(&enc.get_extradata().unwrap());
+            dec.configure().unwrap();
+            assert!(dec.set_option("output_channels", Value::U64(2)).is_err());
+            dec.send_packet(&pkt).unwrap();
+
+            let frame = dec.receive_frame().unwrap();
+            if let MediaKind::Audio(ref info) = frame.kind {
+                assert_eq!(info.map.len(), 1);
+                assert_eq!(info.samples, 960 - delay);
+            } else {
+                unreachable!();
+            }
+        }
+
+        #[test]
+        fn surround_roundtrip() {
+            if !available() {
+                return;
+            }
+            use data::audiosample::ChannelType::*;
+            use data::params::{self, CodecParams};
+
+            // 5.1 in the WAVE channel order
+            let mut map = ChannelMap::new();
+            map.add_channels(&[L, R, C, LFE, Ls, Rs]);
+
+            let mut enc = crate::encoder::OPUS_DESCR.create();
+            enc.set_params(&CodecParams {
+                kind: Some(params::MediaKind::Audio(params::AudioInfo {
+                    rate: 48000,
+                    map: Some(map.clone()),
+                    format: None,
+                })),
+                codec_id: None,
+                extradata: None,
+                bit_rate: 0,
+                convergence_window: 0,
+                delay: 0,
+            })
+            .unwrap();
+            enc.configure().unwrap();
+
+            let extradata = enc.get_extradata().unwrap();
+            assert_eq!(extradata[18], 1);
+            assert_eq!(extradata.len(), 19 + 2 + 6);
+
+            let info = AudioInfo {
+                samples: 960,
+                sample_rate: 48000,
+                map,
+                format: Arc::new(S16),
+                block_len: None,
+            };
+            let frame = Frame::new_default_frame(info, Some(TimeInfo::default()));
+            let pkt = encode(&mut enc, &Arc::new(frame)).remove(0);
+
+            let mut dec = OPUS_DESCR.create();
+            dec.set_extradata(&extradata);
+            dec.configure().unwrap();
+            dec.send_packet(&pkt).unwrap();
+
+            let frame = dec.receive_frame().unwrap();
+            if let MediaKind::Audio(ref info) = frame.kind {
+                assert_eq!(info.map.len(), 6);
+                assert_eq!(info.map.get_channel(5), LFE);
+            } else {
+                unreachable!();
+            }
+
+            // Keep the front channels only
+            let mut dec = OPUS_DESCR.create();
+            dec.set_option("output_channels", Value::U64(2)).unwrap();
+            dec.set_extradata(&extradata);
+            dec.configure().unwrap();
+            dec.send_packet(&pkt).unwrap();
+
+            let frame = dec.receive_frame().unwrap();
+            if let MediaKind::Audio(ref info) = frame.kind {
+                assert_eq!(info.map.len(), 2);
+                assert_eq!(info.map.get_channel(1), R);
+            } else {
+                unreachable!();
+            }
+        }
+    }
 }
 
 #[cfg(feature = "codec-trait")]
-pub use self::decoder_trait::OPUS_DESCR;
+pub use self::decoder_trait::{is_concealed, Concealed, OPUS_DESCR};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoder::tests::{encode, stereo_encoder, stereo_input};
+
+    fn stereo_decoder() -> Decoder {
+        Decoder::create(48000, 2, 1, 1, &[0, 1]).unwrap()
+    }
+
+    #[test]
+    fn cloned_state() {
+        if !available() {
+            return;
+        }
+        let mut enc = stereo_encoder();
+        let mut dec = stereo_decoder();
+        let input = stereo_input();
+        let mut out = vec![0i16; 1920];
+        dec.decode(&encode(&mut enc, &input[..])[..], &mut out[..], false)
+            .unwrap();
+
+        let mut copy = dec.try_clone().unwrap();
+        let mut copied = vec![0i16; 1920];
+        let pkt = encode(&mut enc, &input[..]);
+        dec.decode(&pkt[..], &mut out[..], false).unwrap();
+        copy.decode(&pkt[..], &mut copied[..], false).unwrap();
+        assert_eq!(out, copied);
+    }
+
+    #[test]
+    fn pooled_states() {
+        if !available() {
+            return;
+        }
+        let layout = DecoderLayout {
+            sample_rate: 48000,
+            channels: 2,
+            streams: 1,
+            coupled_streams: 1,
+            mapping: vec![0, 1],
+        };
+        let mut pool = DecoderPool::new(layout, 1).unwrap();
+        let pkt = encode(&mut stereo_encoder(), &stereo_input()[..]);
+
+        let mut dec = pool.get().unwrap();
+        assert_eq!(pool.available(), 0);
+        let mut out = vec![0i16; 1920];
+        dec.decode(&pkt[..], &mut out[..], false).unwrap();
+
+        // Returned states are as good as new
+        pool.put(dec);
+        assert_eq!(pool.available(), 1);
+        let mut fresh = vec![0i16; 1920];
+        let mut dec = pool.get().unwrap();
+        dec.decode(&pkt[..], &mut fresh[..], false).unwrap();
+        assert_eq!(out, fresh);
+    }
+
+    #[test]
+    fn selected_channels() {
+        // 5.1 in the Vorbis order, center and LFE are not coupled
+        let mapping = [0, 4, 1, 2, 3, 5];
+        assert_eq!(
+            select_channels(2, 1, 4, 2, &mapping),
+            Some((4, 2, vec![0, 1]))
+        );
+        assert_eq!(select_channels(1, 1, 4, 2, &mapping), Some((4, 2, vec![4])));
+        assert_eq!(select_channels(3, 1, 4, 2, &mapping), None);
+        assert_eq!(select_channels(2, 255, 4, 2, &mapping), None);
+
+        // Mono and stereo are left for libopus to mix
+        assert_eq!(select_channels(1, 0, 1, 1, &[0, 1]), Some((1, 0, vec![0])));
+        assert_eq!(select_channels(2, 0, 1, 0, &[0]), Some((1, 1, vec![0, 1])));
+    }
+
+    #[test]
+    fn planar_output() {
+        if !available() {
+            return;
+        }
+        let pkt = encode(&mut stereo_encoder(), &stereo_input()[..]);
+        let mut dec = stereo_decoder();
+        let mut copy = dec.try_clone().unwrap();
+
+        let (mut left, mut right) = (vec![0i16; 1000], vec![0i16; 1000]);
+        let mut planes = [&mut left[..], &mut right[..]];
+        assert_eq!(dec.decode(&pkt[..], &mut planes[..], false).unwrap(), 960);
+
+        let mut out = vec![0i16; 2000];
+        assert_eq!(copy.decode(&pkt[..], &mut out[..], false).unwrap(), 960);
+        for i in 0..960 {
+            assert_eq!((planes[0][i], planes[1][i]), (out[2 * i], out[2 * i + 1]));
+        }
+    }
+}
@@ -0,0 +1,182 @@
+//! Deep REDundancy (DRED), available from libopus 1.5.
+//!
+//! An encoder with a DRED duration set, see `Encoder::set_dred_duration`,
+//! embeds a low bitrate neural coding of the recent past in its packets.
+//! After a loss, the first packet received is parsed into a `Dred` by a
+//! `DredDecoder` and the missing audio is rebuilt from it with
+//! `Decoder::decode_dred`.
+
+use crate::common::*;
+use crate::ffi::*;
+
+use std::sync::Arc;
+
+/// Parses the redundancy out of the packets.
+pub struct DredDecoder {
+    dec: *mut OpusDREDDecoder,
+    /// DNN weights the state points into, see `set_dnn_blob`
+    _dnn: Option<Arc<[u8]>>,
+}
+
+unsafe impl Send for DredDecoder {}
+unsafe impl Sync for DredDecoder {}
+
+impl DredDecoder {
+    pub fn create() -> Result<DredDecoder, Error> {
+        loaded(Operation::Create)?;
+
+        if !supports(Capability::Dred) {
+            return Err(Error::invalid(Operation::Create, "DRED needs libopus 1.5"));
+        }
+
+        let mut err = 0;
+        let dec = unsafe { opus_dred_decoder_create(&mut err) };
+
+        if err < 0 {
+            Err(Error::opus(Operation::Create, err))
+        } else {
+            Ok(DredDecoder { dec, _dnn: None })
+        }
+    }
+
+    /// Loads the DNN weights used to decode the redundancy.
+    ///
+    /// Only libopus builds that do not embed the weights need it. libopus
+    /// keeps pointing into `blob`, it is kept alive as long as the decoder.
+    pub fn set_dnn_blob(&mut self, blob: Arc<[u8]>) -> Result<(), Error> {
+        let ret = unsafe {
+            opus_rs_dred_decoder_set_dnn_blob(
+                self.dec,
+                blob.as_ptr() as *const _,
+                blob.len() as i32,
+            )
+        };
+
+        if ret < 0 {
+            Err(Error::opus(Operation::Ctl(OPUS_SET_DNN_BLOB_REQUEST), ret))
+        } else {
+            self._dnn = Some(blob);
+            Ok(())
+        }
+    }
+
+    /// Reads the redundancy carried by `packet` into `dred`, keeping at
+    /// most `max_samples` samples at `sample_rate`.
+    ///
+    /// A packet without redundancy yields an empty `DredRange`.
+    pub fn parse(
+        &mut self,
+        dred: &mut Dred,
+        packet: &[u8],
+        max_samples: usize,
+        sample_rate: usize,
+    ) -> Result<DredRange, Error> {
+        let mut end = 0;
+        let ret = unsafe {
+            opus_dred_parse(
+                self.dec,
+                dred.dred,
+                packet.as_ptr(),
+                packet.len() as i32,
+                max_samples as i32,
+                sample_rate as i32,
+                &mut end,
+                0,
+            )
+        };
+
+        if ret < 0 {
+            Err(Error::opus(Operation::Decode, ret))
+        } else {
+            Ok(DredRange {
+                offset: ret as usize,
+                end: (end as usize).min(ret as usize),
+            })
+        }
+    }
+}
+
+impl Drop for DredDecoder {
+    fn drop(&mut self) {
+        unsafe { opus_dred_decoder_destroy(self.dec) }
+    }
+}
+
+/// The redundancy parsed out of a packet.
+pub struct Dred {
+    dred: *mut OpusDRED,
+}
+
+unsafe impl Send for Dred {}
+unsafe impl Sync for Dred {}
+
+impl Dred {
+    pub fn create() -> Result<Dred, Error> {
+        loaded(Operation::Create)?;
+
+        if !supports(Capability::Dred) {
+            return Err(Error::invalid(Operation::Create, "DRED needs libopus 1.5"));
+        }
+
+        let mut err = 0;
+        let dred = unsafe { opus_dred_alloc(&mut err) };
+
+        if err < 0 {
+            Err(Error::opus(Operation::Create, err))
+        } else {
+            Ok(Dred { dred })
+        }
+    }
+
+    pub(crate) fn as_ptr(&self) -> *const OpusDRED {
+        self.dred
+    }
+}
+
+impl Drop for Dred {
+    fn drop(&mut self) {
+        unsafe { opus_dred_free(self.dred) }
+    }
+}
+
+/// The audio a `Dred` can rebuild, counted in samples before the start of
+/// the packet it was parsed from.
+///
+/// Offsets past `end` and up to `offset` can be passed to
+/// `Decoder::decode_dred`, the most recent `end` samples are not covered.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DredRange {
+    pub offset: usize,
+    pub end: usize,
+}
+
+impl DredRange {
+    pub fn is_empty(&self) -> bool {
+        self.offset <= self.end
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_without_redundancy() {
+        if !available() {
+            return;
+        }
+        let mut dec = DredDecoder::create().unwrap();
+        let mut dred = Dred::create().unwrap();
+
+        // A CELT-only packet carries no extension
+        let range = dec
+            .parse(&mut dred, &[0xfc, 0xff, 0xfe], 48000, 48000)
+            .unwrap();
+        assert!(range.is_empty());
+        assert!(!DredRange {
+            offset: 960,
+            end: 0
+        }
+        .is_empty());
+    }
+}
@@ -1,10 +1,27 @@
 use crate::common::*;
 use crate::ffi::*;
+use crate::planar::Scratch;
+use std::ptr;
 use std::str::FromStr;
+#[cfg(feature = "dred")]
+use std::sync::Arc;
 
 pub struct Encoder {
     enc: *mut OpusMSEncoder,
+    sample_rate: usize,
     channels: usize,
+    streams: usize,
+    coupled_streams: usize,
+    mapping: Vec<u8>,
+    /// Mapping family for the encoders created by `create_surround`
+    mapping_family: Option<u8>,
+    /// Memory the state lives in, if not allocated by libopus
+    mem: Option<StateMemory>,
+    /// Interleaved copy of planar input
+    scratch: Scratch,
+    /// DNN weights the state points into, see `set_dnn_blob`
+    #[cfg(feature = "dred")]
+    dnn: Option<Arc<[u8]>>,
 }
 
 mod constants {
@@ -12,6 +29,8 @@ mod constants {
     pub use ffi::OPUS_SET_BANDWIDTH_REQUEST;
     pub use ffi::OPUS_SET_BITRATE_REQUEST;
     pub use ffi::OPUS_SET_COMPLEXITY_REQUEST;
+    #[cfg(feature = "dred")]
+    pub use ffi::OPUS_SET_DRED_DURATION_REQUEST;
     pub use ffi::OPUS_SET_DTX_REQUEST;
     pub use ffi::OPUS_SET_EXPERT_FRAME_DURATION_REQUEST;
     pub use ffi::OPUS_SET_FORCE_CHANNELS_REQUEST;
@@ -20,13 +39,35 @@ mod constants {
     pub use ffi::OPUS_SET_LSB_DEPTH_REQUEST;
     pub use ffi::OPUS_SET_MAX_BANDWIDTH_REQUEST;
     pub use ffi::OPUS_SET_PACKET_LOSS_PERC_REQUEST;
+    pub use ffi::OPUS_SET_PHASE_INVERSION_DISABLED_REQUEST;
     pub use ffi::OPUS_SET_PREDICTION_DISABLED_REQUEST;
     pub use ffi::OPUS_SET_SIGNAL_REQUEST;
     pub use ffi::OPUS_SET_VBR_CONSTRAINT_REQUEST;
     pub use ffi::OPUS_SET_VBR_REQUEST;
 
+    pub use ffi::OPUS_GET_APPLICATION_REQUEST;
+    pub use ffi::OPUS_GET_BANDWIDTH_REQUEST;
+    pub use ffi::OPUS_GET_BITRATE_REQUEST;
+    pub use ffi::OPUS_GET_COMPLEXITY_REQUEST;
+    #[cfg(feature = "dred")]
+    pub use ffi::OPUS_GET_DRED_DURATION_REQUEST;
+    pub use ffi::OPUS_GET_DTX_REQUEST;
+    pub use ffi::OPUS_GET_EXPERT_FRAME_DURATION_REQUEST;
     pub use ffi::OPUS_GET_FINAL_RANGE_REQUEST;
+    pub use ffi::OPUS_GET_FORCE_CHANNELS_REQUEST;
+    pub use ffi::OPUS_GET_INBAND_FEC_REQUEST;
     pub use ffi::OPUS_GET_LOOKAHEAD_REQUEST;
+    pub use ffi::OPUS_GET_LSB_DEPTH_REQUEST;
+    pub use ffi::OPUS_GET_PACKET_LOSS_PERC_REQUEST;
+    pub use ffi::OPUS_GET_PHASE_INVERSION_DISABLED_REQUEST;
+    pub use ffi::OPUS_GET_PREDICTION_DISABLED_REQUEST;
+    pub use ffi::OPUS_GET_SAMPLE_RATE_REQUEST;
+    pub use ffi::OPUS_GET_SIGNAL_REQUEST;
+    pub use ffi::OPUS_GET_VBR_CONSTRAINT_REQUEST;
+    pub use ffi::OPUS_GET_VBR_REQUEST;
+
+    pub use ffi::OPUS_AUTO;
+    pub use ffi::OPUS_BITRATE_MAX;
 
     pub use ffi::OPUS_BANDWIDTH_FULLBAND;
     pub use ffi::OPUS_BANDWIDTH_MEDIUMBAND;
@@ -43,6 +84,8 @@ mod constants {
     pub use ffi::OPUS_FRAMESIZE_60_MS;
     pub use ffi::OPUS_FRAMESIZE_80_MS;
     pub use ffi::OPUS_FRAMESIZE_ARG;
+    pub use ffi::OPUS_SIGNAL_MUSIC;
+    pub use ffi::OPUS_SIGNAL_VOICE;
 }
 
 pub use self::constants::*;
@@ -71,6 +114,112 @@ impl FromStr for Application {
     }
 }
 
+#[repr(i32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Bandwidth {
+    Auto = OPUS_AUTO,
+    Narrowband = OPUS_BANDWIDTH_NARROWBAND as i32,
+    Mediumband = OPUS_BANDWIDTH_MEDIUMBAND as i32,
+    Wideband = OPUS_BANDWIDTH_WIDEBAND as i32,
+    Superwideband = OPUS_BANDWIDTH_SUPERWIDEBAND as i32,
+    Fullband = OPUS_BANDWIDTH_FULLBAND as i32,
+}
+
+impl FromStr for Bandwidth {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use self::Bandwidth::*;
+        match s {
+            "auto" => Ok(Auto),
+            "narrowband" => Ok(Narrowband),
+            "mediumband" => Ok(Mediumband),
+            "wideband" => Ok(Wideband),
+            "superwideband" => Ok(Superwideband),
+            "fullband" => Ok(Fullband),
+            _ => Err(()),
+        }
+    }
+}
+
+#[repr(i32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Signal {
+    Auto = OPUS_AUTO,
+    Voice = OPUS_SIGNAL_VOICE as i32,
+    Music = OPUS_SIGNAL_MUSIC as i32,
+}
+
+impl FromStr for Signal {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use self::Signal::*;
+        match s {
+            "auto" => Ok(Auto),
+            "voice" => Ok(Voice),
+            "music" => Ok(Music),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Duration of the frames fed to the encoder.
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FrameDuration {
+    Ms2_5 = OPUS_FRAMESIZE_2_5_MS,
+    Ms5 = OPUS_FRAMESIZE_5_MS,
+    Ms10 = OPUS_FRAMESIZE_10_MS,
+    Ms20 = OPUS_FRAMESIZE_20_MS,
+    Ms40 = OPUS_FRAMESIZE_40_MS,
+    Ms60 = OPUS_FRAMESIZE_60_MS,
+    Ms80 = OPUS_FRAMESIZE_80_MS,
+    Ms100 = OPUS_FRAMESIZE_100_MS,
+    Ms120 = OPUS_FRAMESIZE_120_MS,
+}
+
+impl FrameDuration {
+    /// Number of samples per channel in a frame at `sample_rate`.
+    pub fn samples(self, sample_rate: usize) -> usize {
+        use self::FrameDuration::*;
+        // In tenths of millisecond
+        let duration = match self {
+            Ms2_5 => 25,
+            Ms5 => 50,
+            Ms10 => 100,
+            Ms20 => 200,
+            Ms40 => 400,
+            Ms60 => 600,
+            Ms80 => 800,
+            Ms100 => 1000,
+            Ms120 => 1200,
+        };
+
+        sample_rate * duration / 10000
+    }
+}
+
+impl FromStr for FrameDuration {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use self::FrameDuration::*;
+        match s {
+            "2.5" => Ok(Ms2_5),
+            "5" => Ok(Ms5),
+            "10" => Ok(Ms10),
+            "20" => Ok(Ms20),
+            "40" => Ok(Ms40),
+            "60" => Ok(Ms60),
+            "80" => Ok(Ms80),
+            "100" => Ok(Ms100),
+            "120" => Ok(Ms120),
+            _ => Err(()),
+        }
+    }
+}
+
 impl Encoder {
     pub fn create(
         sample_rate: usize,
@@ -79,7 +228,13 @@ impl Encoder {
         coupled_streams: usize,
         mapping: &[u8],
         application: Application,
-    ) -> Result<Encoder, ErrorCode> {
+    ) -> Result<Encoder, Error> {
+        loaded(Operation::Create)?;
+
+        if mapping.len() < channels {
+            return Err(Error::invalid(Operation::Create, "mapping too short"));
+        }
+
         let mut err = 0;
         let enc = unsafe {
             opus_multistream_encoder_create(
@@ -94,112 +249,651 @@ impl Encoder {
         };
 
         if err < 0 {
-            Err(err.into())
+            Err(Error::opus(Operation::Create, err))
+        } else {
+            Ok(Encoder {
+                enc,
+                sample_rate,
+                channels,
+                streams,
+                coupled_streams,
+                mapping: mapping[..channels].to_vec(),
+                mapping_family: None,
+                mem: None,
+                scratch: Scratch::default(),
+                #[cfg(feature = "dred")]
+                dnn: None,
+            })
+        }
+    }
+
+    /// Creates an encoder for a channel mapping family, letting libopus pick
+    /// the stream layout.
+    ///
+    /// Family 0 covers mono and stereo, family 1 the Vorbis surround layouts
+    /// up to 8 channels and family 255 any number of discrete channels.
+    /// The input is expected in the channel order of the family.
+    pub fn create_surround(
+        sample_rate: usize,
+        channels: usize,
+        mapping_family: u8,
+        application: Application,
+    ) -> Result<Encoder, Error> {
+        loaded(Operation::Create)?;
+
+        let mut err = 0;
+        let mut streams = 0;
+        let mut coupled_streams = 0;
+        let mut mapping = vec![0u8; channels];
+        let enc = unsafe {
+            opus_multistream_surround_encoder_create(
+                sample_rate as i32,
+                channels as i32,
+                mapping_family as i32,
+                &mut streams,
+                &mut coupled_streams,
+                mapping.as_mut_ptr(),
+                application as i32,
+                &mut err,
+            )
+        };
+
+        if err < 0 {
+            Err(Error::opus(Operation::Create, err))
         } else {
-            Ok(Encoder { enc, channels })
+            Ok(Encoder {
+                enc,
+                sample_rate,
+                channels,
+                streams: streams as usize,
+                coupled_streams: coupled_streams as usize,
+                mapping,
+                mapping_family: Some(mapping_family),
+                mem: None,
+                scratch: Scratch::default(),
+                #[cfg(feature = "dred")]
+                dnn: None,
+            })
         }
     }
 
-    pub fn encode<'a, I>(&mut self, input: I, output: &mut [u8]) -> Result<usize, ErrorCode>
+    /// Like `create`, initializing the state in `mem` instead of allocating.
+    ///
+    /// `mem` must be at least `Encoder::state_size(streams, coupled_streams)`
+    /// bytes long.
+    pub fn create_in(
+        mut mem: StateMemory,
+        sample_rate: usize,
+        channels: usize,
+        streams: usize,
+        coupled_streams: usize,
+        mapping: &[u8],
+        application: Application,
+    ) -> Result<Encoder, Error> {
+        loaded(Operation::Create)?;
+
+        let size = Encoder::state_size(streams, coupled_streams)?;
+        if mapping.len() < channels {
+            return Err(Error::invalid(Operation::Create, "mapping too short"));
+        }
+        if mem.len() < size {
+            return Err(Error::invalid(Operation::Create, "state memory too small"));
+        }
+
+        let enc = mem.as_mut_ptr() as *mut OpusMSEncoder;
+        let err = unsafe {
+            opus_multistream_encoder_init(
+                enc,
+                sample_rate as i32,
+                channels as i32,
+                streams as i32,
+                coupled_streams as i32,
+                mapping.as_ptr(),
+                application as i32,
+            )
+        };
+
+        if err < 0 {
+            Err(Error::opus(Operation::Create, err))
+        } else {
+            Ok(Encoder {
+                enc,
+                sample_rate,
+                channels,
+                streams,
+                coupled_streams,
+                mapping: mapping[..channels].to_vec(),
+                mapping_family: None,
+                mem: Some(mem),
+                scratch: Scratch::default(),
+                #[cfg(feature = "dred")]
+                dnn: None,
+            })
+        }
+    }
+
+    /// Like `create_surround`, initializing the state in `mem` instead of
+    /// allocating.
+    ///
+    /// `mem` must be at least
+    /// `Encoder::surround_state_size(channels, mapping_family)` bytes long.
+    pub fn create_surround_in(
+        mut mem: StateMemory,
+        sample_rate: usize,
+        channels: usize,
+        mapping_family: u8,
+        application: Application,
+    ) -> Result<Encoder, Error> {
+        loaded(Operation::Create)?;
+
+        let size = Encoder::surround_state_size(channels, mapping_family)?;
+        if mem.len() < size {
+            return Err(Error::invalid(Operation::Create, "state memory too small"));
+        }
+
+        let mut streams = 0;
+        let mut coupled_streams = 0;
+        let mut mapping = vec![0u8; channels];
+        let enc = mem.as_mut_ptr() as *mut OpusMSEncoder;
+        let err = unsafe {
+            opus_multistream_surround_encoder_init(
+                enc,
+                sample_rate as i32,
+                channels as i32,
+                mapping_family as i32,
+                &mut streams,
+                &mut coupled_streams,
+                mapping.as_mut_ptr(),
+                application as i32,
+            )
+        };
+
+        if err < 0 {
+            Err(Error::opus(Operation::Create, err))
+        } else {
+            Ok(Encoder {
+                enc,
+                sample_rate,
+                channels,
+                streams: streams as usize,
+                coupled_streams: coupled_streams as usize,
+                mapping,
+                mapping_family: Some(mapping_family),
+                mem: Some(mem),
+                scratch: Scratch::default(),
+                #[cfg(feature = "dred")]
+                dnn: None,
+            })
+        }
+    }
+
+    /// Bytes needed by the state of an encoder created with `create_in`.
+    pub fn state_size(streams: usize, coupled_streams: usize) -> Result<usize, Error> {
+        loaded(Operation::Create)?;
+
+        let size =
+            unsafe { opus_multistream_encoder_get_size(streams as i32, coupled_streams as i32) };
+        if size <= 0 {
+            return Err(Error::invalid(Operation::Create, "invalid stream layout"));
+        }
+
+        Ok(size as usize)
+    }
+
+    /// Bytes needed by the state of an encoder created with
+    /// `create_surround_in`.
+    pub fn surround_state_size(channels: usize, mapping_family: u8) -> Result<usize, Error> {
+        loaded(Operation::Create)?;
+
+        let size = unsafe {
+            opus_multistream_surround_encoder_get_size(channels as i32, mapping_family as i32)
+        };
+        if size <= 0 {
+            return Err(Error::invalid(Operation::Create, "invalid stream layout"));
+        }
+
+        Ok(size as usize)
+    }
+
+    /// Gives back the memory of an encoder created with `create_in` or
+    /// `create_surround_in`.
+    pub fn into_memory(mut self) -> Option<StateMemory> {
+        let mem = self.mem.take();
+        if mem.is_some() {
+            self.enc = ptr::null_mut();
+        }
+        mem
+    }
+
+    /// Size in bytes of the libopus state.
+    fn size(&self) -> usize {
+        let size = unsafe {
+            match self.mapping_family {
+                Some(family) => {
+                    opus_multistream_surround_encoder_get_size(self.channels as i32, family as i32)
+                }
+                None => opus_multistream_encoder_get_size(
+                    self.streams as i32,
+                    self.coupled_streams as i32,
+                ),
+            }
+        };
+
+        size as usize
+    }
+
+    pub fn sample_rate(&self) -> usize {
+        self.sample_rate
+    }
+
+    pub fn channels(&self) -> usize {
+        self.channels
+    }
+
+    pub fn streams(&self) -> usize {
+        self.streams
+    }
+
+    pub fn coupled_streams(&self) -> usize {
+        self.coupled_streams
+    }
+
+    /// Stream channel each input channel is coded into.
+    pub fn mapping(&self) -> &[u8] {
+        &self.mapping
+    }
+
+    pub fn encode<'a, I>(&mut self, input: I, output: &mut [u8]) -> Result<usize, Error>
     where
         I: Into<AudioBuffer<'a>>,
     {
-        let ret = match input.into() {
-            AudioBuffer::F32(v) => unsafe {
+        let (enc, channels) = (self.enc, self.channels);
+        let (data, max) = (output.as_mut_ptr(), output.len() as i32);
+        let ret = input.into().interleaved(
+            &mut self.scratch,
+            channels,
+            Operation::Encode,
+            |v| unsafe {
                 opus_multistream_encode_float(
-                    self.enc,
+                    enc,
                     v.as_ptr(),
-                    (v.len() / self.channels) as i32,
-                    output.as_mut_ptr(),
-                    output.len() as i32,
+                    (v.len() / channels) as i32,
+                    data,
+                    max,
                 )
             },
-            AudioBuffer::I16(v) => unsafe {
-                opus_multistream_encode(
-                    self.enc,
-                    v.as_ptr(),
-                    (v.len() / self.channels) as i32,
-                    output.as_mut_ptr(),
-                    output.len() as i32,
-                )
+            |v| unsafe {
+                opus_multistream_encode(enc, v.as_ptr(), (v.len() / channels) as i32, data, max)
             },
-        };
+        )?;
 
         if ret < 0 {
-            Err(ret.into())
+            Err(Error::opus(Operation::Encode, ret))
         } else {
             Ok(ret as usize)
         }
     }
 
-    pub fn set_option(&mut self, key: u32, val: u32) -> Result<(), ErrorCode> {
-        let ret = match key {
-            OPUS_SET_APPLICATION_REQUEST |
-            OPUS_SET_BITRATE_REQUEST |
-            OPUS_SET_MAX_BANDWIDTH_REQUEST |
-            OPUS_SET_VBR_REQUEST |
-            OPUS_SET_BANDWIDTH_REQUEST |
-            OPUS_SET_COMPLEXITY_REQUEST |
-            OPUS_SET_INBAND_FEC_REQUEST |
-            OPUS_SET_PACKET_LOSS_PERC_REQUEST |
-            OPUS_SET_DTX_REQUEST |
-            OPUS_SET_VBR_CONSTRAINT_REQUEST |
-            OPUS_SET_FORCE_CHANNELS_REQUEST |
-            OPUS_SET_SIGNAL_REQUEST |
-            OPUS_SET_GAIN_REQUEST |
-            OPUS_SET_LSB_DEPTH_REQUEST |
-            OPUS_SET_EXPERT_FRAME_DURATION_REQUEST |
-            OPUS_SET_PREDICTION_DISABLED_REQUEST /* |
-            OPUS_SET_PHASE_INVERSION_DISABLED_REQUEST */ => unsafe {
-                opus_multistream_encoder_ctl(self.enc, key as i32, val)
-            },
-            _ => unimplemented!(),
+    pub fn set_option(&mut self, key: u32, val: u32) -> Result<(), Error> {
+        let (enc, val) = (self.enc, val as i32);
+        let ret = unsafe {
+            match key {
+                OPUS_SET_APPLICATION_REQUEST => {
+                    opus_rs_multistream_encoder_set_application(enc, val)
+                }
+                OPUS_SET_BITRATE_REQUEST => opus_rs_multistream_encoder_set_bitrate(enc, val),
+                OPUS_SET_MAX_BANDWIDTH_REQUEST => {
+                    opus_rs_multistream_encoder_set_max_bandwidth(enc, val)
+                }
+                OPUS_SET_VBR_REQUEST => opus_rs_multistream_encoder_set_vbr(enc, val),
+                OPUS_SET_BANDWIDTH_REQUEST => opus_rs_multistream_encoder_set_bandwidth(enc, val),
+                OPUS_SET_COMPLEXITY_REQUEST => opus_rs_multistream_encoder_set_complexity(enc, val),
+                OPUS_SET_INBAND_FEC_REQUEST => opus_rs_multistream_encoder_set_inband_fec(enc, val),
+                OPUS_SET_PACKET_LOSS_PERC_REQUEST => {
+                    opus_rs_multistream_encoder_set_packet_loss_perc(enc, val)
+                }
+                OPUS_SET_DTX_REQUEST => opus_rs_multistream_encoder_set_dtx(enc, val),
+                OPUS_SET_VBR_CONSTRAINT_REQUEST => {
+                    opus_rs_multistream_encoder_set_vbr_constraint(enc, val)
+                }
+                OPUS_SET_FORCE_CHANNELS_REQUEST => {
+                    opus_rs_multistream_encoder_set_force_channels(enc, val)
+                }
+                OPUS_SET_SIGNAL_REQUEST => opus_rs_multistream_encoder_set_signal(enc, val),
+                OPUS_SET_LSB_DEPTH_REQUEST => opus_rs_multistream_encoder_set_lsb_depth(enc, val),
+                OPUS_SET_EXPERT_FRAME_DURATION_REQUEST => {
+                    opus_rs_multistream_encoder_set_expert_frame_duration(enc, val)
+                }
+                OPUS_SET_PREDICTION_DISABLED_REQUEST => {
+                    opus_rs_multistream_encoder_set_prediction_disabled(enc, val)
+                }
+                OPUS_SET_PHASE_INVERSION_DISABLED_REQUEST => {
+                    opus_rs_multistream_encoder_set_phase_inversion_disabled(enc, val)
+                }
+                // The multistream encoder does not forward it, set every stream
+                #[cfg(feature = "dred")]
+                OPUS_SET_DRED_DURATION_REQUEST => {
+                    return self.each_stream(key, |st| opus_rs_encoder_set_dred_duration(st, val))
+                }
+                _ => return Err(Error::invalid(Operation::Ctl(key), "unsupported request")),
+            }
         };
 
         if ret < 0 {
-            Err(ret.into())
+            Err(Error::opus(Operation::Ctl(key), ret))
         } else {
             Ok(())
         }
     }
-    pub fn get_option(&self, key: u32) -> Result<i32, ErrorCode> {
+    pub fn get_option(&self, key: u32) -> Result<i32, Error> {
+        let enc = self.enc;
         let mut val: i32 = 0;
-        let ret = match key {
-            OPUS_GET_LOOKAHEAD_REQUEST | OPUS_GET_FINAL_RANGE_REQUEST => unsafe {
-                opus_multistream_encoder_ctl(self.enc, key as i32, &mut val as *mut i32)
-            },
-            _ => unimplemented!(),
+        let ret = unsafe {
+            let v = &mut val as *mut i32;
+            match key {
+                OPUS_GET_APPLICATION_REQUEST => opus_rs_multistream_encoder_get_application(enc, v),
+                OPUS_GET_BITRATE_REQUEST => opus_rs_multistream_encoder_get_bitrate(enc, v),
+                OPUS_GET_VBR_REQUEST => opus_rs_multistream_encoder_get_vbr(enc, v),
+                OPUS_GET_BANDWIDTH_REQUEST => opus_rs_multistream_encoder_get_bandwidth(enc, v),
+                OPUS_GET_COMPLEXITY_REQUEST => opus_rs_multistream_encoder_get_complexity(enc, v),
+                OPUS_GET_INBAND_FEC_REQUEST => opus_rs_multistream_encoder_get_inband_fec(enc, v),
+                OPUS_GET_PACKET_LOSS_PERC_REQUEST => {
+                    opus_rs_multistream_encoder_get_packet_loss_perc(enc, v)
+                }
+                OPUS_GET_DTX_REQUEST => opus_rs_multistream_encoder_get_dtx(enc, v),
+                OPUS_GET_VBR_CONSTRAINT_REQUEST => {
+                    opus_rs_multistream_encoder_get_vbr_constraint(enc, v)
+                }
+                OPUS_GET_FORCE_CHANNELS_REQUEST => {
+                    opus_rs_multistream_encoder_get_force_channels(enc, v)
+                }
+                OPUS_GET_SIGNAL_REQUEST => opus_rs_multistream_encoder_get_signal(enc, v),
+                OPUS_GET_LOOKAHEAD_REQUEST => opus_rs_multistream_encoder_get_lookahead(enc, v),
+                OPUS_GET_SAMPLE_RATE_REQUEST => opus_rs_multistream_encoder_get_sample_rate(enc, v),
+                OPUS_GET_FINAL_RANGE_REQUEST => {
+                    opus_rs_multistream_encoder_get_final_range(enc, v as *mut u32)
+                }
+                OPUS_GET_LSB_DEPTH_REQUEST => opus_rs_multistream_encoder_get_lsb_depth(enc, v),
+                OPUS_GET_EXPERT_FRAME_DURATION_REQUEST => {
+                    opus_rs_multistream_encoder_get_expert_frame_duration(enc, v)
+                }
+                OPUS_GET_PREDICTION_DISABLED_REQUEST => {
+                    opus_rs_multistream_encoder_get_prediction_disabled(enc, v)
+                }
+                OPUS_GET_PHASE_INVERSION_DISABLED_REQUEST => {
+                    opus_rs_multistream_encoder_get_phase_inversion_disabled(enc, v)
+                }
+                #[cfg(feature = "dred")]
+                OPUS_GET_DRED_DURATION_REQUEST => {
+                    let mut st = ptr::null_mut();
+                    match opus_rs_multistream_encoder_get_encoder_state(enc, 0, &mut st) {
+                        ret if ret < 0 => ret,
+                        _ => opus_rs_encoder_get_dred_duration(st, v),
+                    }
+                }
+                _ => return Err(Error::invalid(Operation::Ctl(key), "unsupported request")),
+            }
         };
 
         if ret < 0 {
-            Err(ret.into())
+            Err(Error::opus(Operation::Ctl(key), ret))
         } else {
             Ok(val)
         }
     }
 
     pub fn reset(&mut self) {
-        let _ = unsafe { opus_multistream_encoder_ctl(self.enc, OPUS_RESET_STATE as i32) };
+        let _ = unsafe { opus_rs_multistream_encoder_reset_state(self.enc) };
+    }
+
+    /// Copies the whole encoder state, the copy continues encoding exactly
+    /// where the original is.
+    pub fn try_clone(&self) -> Result<Encoder, Error> {
+        // Allocate and initialize a state with the same layout, the settings
+        // are overwritten by the copy.
+        let enc = match self.mapping_family {
+            Some(family) => Encoder::create_surround(
+                self.sample_rate,
+                self.channels,
+                family,
+                Application::Audio,
+            )?,
+            None => Encoder::create(
+                self.sample_rate,
+                self.channels,
+                self.streams,
+                self.coupled_streams,
+                &self.mapping,
+                Application::Audio,
+            )?,
+        };
+
+        unsafe {
+            ptr::copy_nonoverlapping(self.enc as *const u8, enc.enc as *mut u8, self.size());
+        }
+
+        // The copied state points into the same weights
+        #[cfg(feature = "dred")]
+        let enc = {
+            let mut enc = enc;
+            enc.dnn = self.dnn.clone();
+            enc
+        };
+
+        Ok(enc)
+    }
+}
+
+/// Deep REDundancy, available from libopus 1.5.
+#[cfg(feature = "dred")]
+impl Encoder {
+    /// Calls `f` on the state of every stream, stopping at the first error.
+    fn each_stream<F>(&mut self, key: u32, mut f: F) -> Result<(), Error>
+    where
+        F: FnMut(*mut OpusEncoder) -> i32,
+    {
+        for id in 0..self.streams {
+            let mut st = ptr::null_mut();
+            let ret = unsafe {
+                opus_rs_multistream_encoder_get_encoder_state(self.enc, id as i32, &mut st)
+            };
+            let ret = if ret < 0 { ret } else { f(st) };
+
+            if ret < 0 {
+                return Err(Error::opus(Operation::StreamCtl(key, id), ret));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Sets how much audio, in 10 ms units up to 104, every packet carries
+    /// as DRED, 0 disables it.
+    ///
+    /// The redundancy is only coded when the expected packet loss, set with
+    /// `OPUS_SET_PACKET_LOSS_PERC_REQUEST`, is not zero and the bitrate
+    /// leaves room for it.
+    pub fn set_dred_duration(&mut self, duration: usize) -> Result<(), Error> {
+        let key = OPUS_SET_DRED_DURATION_REQUEST;
+
+        if !supports(Capability::Dred) {
+            return Err(Error::invalid(
+                Operation::Ctl(key),
+                "DRED needs libopus 1.5",
+            ));
+        }
+
+        self.set_option(key, duration as u32)
+    }
+
+    /// The DRED duration, in 10 ms units.
+    pub fn dred_duration(&self) -> Result<usize, Error> {
+        self.get_option(OPUS_GET_DRED_DURATION_REQUEST)
+            .map(|v| v as usize)
+    }
+
+    /// Loads the DNN weights used by the DRED encoder.
+    /// The blob is kept alive with the state, see `DredDecoder::set_dnn_blob`.
+    pub fn set_dnn_blob(&mut self, blob: Arc<[u8]>) -> Result<(), Error> {
+        let key = OPUS_SET_DNN_BLOB_REQUEST;
+
+        if !supports(Capability::Dred) {
+            return Err(Error::invalid(
+                Operation::Ctl(key),
+                "DRED needs libopus 1.5",
+            ));
+        }
+
+        let (data, len) = (blob.as_ptr() as *const _, blob.len() as i32);
+        self.each_stream(key, |st| unsafe {
+            opus_rs_encoder_set_dnn_blob(st, data, len)
+        })?;
+        self.dnn = Some(blob);
+
+        Ok(())
     }
 }
 
 impl Drop for Encoder {
     fn drop(&mut self) {
-        unsafe { opus_multistream_encoder_destroy(self.enc) };
+        // States living in a StateMemory are freed along with it
+        if self.mem.is_none() && !self.enc.is_null() {
+            unsafe { opus_multistream_encoder_destroy(self.enc) };
+        }
+    }
+}
+
+/// Layout of the encoders handed out by an `EncoderPool`.
+#[derive(Clone, Debug)]
+pub struct EncoderLayout {
+    pub sample_rate: usize,
+    pub channels: usize,
+    pub streams: usize,
+    pub coupled_streams: usize,
+    pub mapping: Vec<u8>,
+    pub application: Application,
+}
+
+/// Pool of pre-initialized encoders sharing the same layout.
+///
+/// Encoders returned to the pool are initialized again in place, so `get`
+/// hands out encoders in their default state without allocating as long
+/// as the pool is not empty.
+pub struct EncoderPool {
+    layout: EncoderLayout,
+    free: Vec<Encoder>,
+}
+
+impl EncoderPool {
+    /// Creates a pool holding `count` ready encoders.
+    pub fn new(layout: EncoderLayout, count: usize) -> Result<EncoderPool, Error> {
+        let mut pool = EncoderPool {
+            layout,
+            free: Vec::with_capacity(count),
+        };
+
+        for _ in 0..count {
+            let enc = pool.create()?;
+            pool.free.push(enc);
+        }
+
+        Ok(pool)
+    }
+
+    /// Creates a pool with an encoder in each of `memory`, e.g. caller
+    /// buffers wrapped by `StateMemory::from_boxed`.
+    ///
+    /// Once they are all handed out, `get` allocates new ones.
+    pub fn with_memory<I>(layout: EncoderLayout, memory: I) -> Result<EncoderPool, Error>
+    where
+        I: IntoIterator<Item = StateMemory>,
+    {
+        let memory = memory.into_iter();
+        let mut pool = EncoderPool {
+            layout,
+            free: Vec::with_capacity(memory.size_hint().0),
+        };
+
+        for mem in memory {
+            let enc = pool.create_in(mem)?;
+            pool.free.push(enc);
+        }
+
+        Ok(pool)
+    }
+
+    fn create(&self) -> Result<Encoder, Error> {
+        let l = &self.layout;
+        let mem = StateMemory::new(Encoder::state_size(l.streams, l.coupled_streams)?);
+
+        self.create_in(mem)
+    }
+
+    fn create_in(&self, mem: StateMemory) -> Result<Encoder, Error> {
+        let l = &self.layout;
+
+        Encoder::create_in(
+            mem,
+            l.sample_rate,
+            l.channels,
+            l.streams,
+            l.coupled_streams,
+            &l.mapping,
+            l.application,
+        )
+    }
+
+    pub fn layout(&self) -> &EncoderLayout {
+        &self.layout
+    }
+
+    /// Number of encoders ready to be handed out.
+    pub fn available(&self) -> usize {
+        self.free.len()
+    }
+
+    /// Takes an encoder from the pool, creating one if the pool is empty.
+    pub fn get(&mut self) -> Result<Encoder, Error> {
+        match self.free.pop() {
+            Some(enc) => Ok(enc),
+            None => self.create(),
+        }
+    }
+
+    /// Returns an encoder to the pool, encoders with a different layout are
+    /// dropped.
+    pub fn put(&mut self, enc: Encoder) {
+        let l = &self.layout;
+        if enc.mapping_family.is_some()
+            || enc.channels != l.channels
+            || enc.streams != l.streams
+            || enc.coupled_streams != l.coupled_streams
+            || enc.mapping != l.mapping
+        {
+            return;
+        }
+
+        if let Some(mem) = enc.into_memory() {
+            if let Ok(enc) = self.create_in(mem) {
+                self.free.push(enc);
+            }
+        }
     }
 }
 
 #[cfg(feature = "codec-trait")]
 mod encoder_trait {
     use super::constants::*;
-    use super::Application;
     use super::Encoder as OpusEncoder;
+    use super::{Application, Bandwidth, FrameDuration, Signal};
     // use std::rc::Rc;
+    use crate::common::channel_map::{vorbis_map, vorbis_order};
+    use crate::common::AudioBuffer;
     use codec::encoder::*;
     use codec::error::*;
-    use data::audiosample::formats::S16;
-    use data::audiosample::ChannelMap;
-    use data::frame::{ArcFrame, FrameBufferConv, MediaKind};
+    use data::audiosample::formats::{F32, S16};
+    use data::audiosample::{ChannelMap, Soniton};
+    use data::frame::{ArcFrame, Frame, FrameBufferConv, MediaKind};
     use data::packet::Packet;
     use data::params::CodecParams;
     use data::rational::Rational64;
@@ -208,6 +902,118 @@ mod encoder_trait {
 
     pub struct Des {
         descr: Descr,
+        formats: &'static [Soniton],
+    }
+
+    impl Des {
+        /// Sample formats accepted by the encoder.
+        pub fn formats(&self) -> &[Soniton] {
+            self.formats
+        }
+    }
+
+    /// Interleaved samples, in the input format.
+    enum Samples {
+        I16(Vec<i16>),
+        F32(Vec<f32>),
+    }
+
+    impl Samples {
+        fn format(&self) -> Soniton {
+            match self {
+                Samples::I16(_) => S16,
+                Samples::F32(_) => F32,
+            }
+        }
+
+        fn len(&self) -> usize {
+            match self {
+                Samples::I16(v) => v.len(),
+                Samples::F32(v) => v.len(),
+            }
+        }
+
+        fn is_empty(&self) -> bool {
+            self.len() == 0
+        }
+
+        fn clear(&mut self) {
+            match self {
+                Samples::I16(v) => v.clear(),
+                Samples::F32(v) => v.clear(),
+            }
+        }
+
+        /// Zero-pads the samples up to `len`.
+        fn pad(&mut self, len: usize) {
+            match self {
+                Samples::I16(v) if v.len() < len => v.resize(len, 0),
+                Samples::F32(v) if v.len() < len => v.resize(len, 0.0),
+                _ => {}
+            }
+        }
+
+        fn consume(&mut self, len: usize) {
+            match self {
+                Samples::I16(v) => drop(v.drain(..len)),
+                Samples::F32(v) => drop(v.drain(..len)),
+            }
+        }
+
+        fn as_buffer(&self, len: usize) -> AudioBuffer<'_> {
+            match self {
+                Samples::I16(v) => AudioBuffer::I16(&v[..len]),
+                Samples::F32(v) => AudioBuffer::F32(&v[..len]),
+            }
+        }
+
+        /// Appends the first `len` samples of `frame`, reordering the
+        /// channels if needed.
+        fn extend(
+            &mut self,
+            frame: &Frame,
+            len: usize,
+            channels: usize,
+            reorder: Option<&[usize]>,
+        ) -> Result<()> {
+            match self {
+                Samples::I16(v) => append(v, frame.buf.as_slice(0), len, channels, reorder),
+                Samples::F32(v) => append(v, frame.buf.as_slice(0), len, channels, reorder),
+            }
+        }
+    }
+
+    fn append<T: Copy, E>(
+        buf: &mut Vec<T>,
+        input: std::result::Result<&[T], E>,
+        len: usize,
+        channels: usize,
+        reorder: Option<&[usize]>,
+    ) -> Result<()> {
+        let input = input.map_err(|_e| Error::InvalidData)?;
+        if input.len() < len {
+            return Err(Error::InvalidData);
+        }
+
+        if let Some(order) = reorder {
+            for samples in input[..len].chunks_exact(channels) {
+                buf.extend(order.iter().map(|&i| samples[i]));
+            }
+        } else {
+            buf.extend_from_slice(&input[..len]);
+        }
+
+        Ok(())
+    }
+
+    fn parse_format(format: &Soniton) -> Result<Samples> {
+        if *format == S16 {
+            Ok(Samples::I16(Vec::new()))
+        } else if *format == F32 {
+            Ok(Samples::F32(Vec::new()))
+        } else {
+            Err(Error::Unsupported("Unsupported sample format".to_owned()))
+        }
     }
 
     struct Cfg {
@@ -215,15 +1021,66 @@ mod encoder_trait {
         streams: usize,
         coupled_streams: usize,
         mapping: Vec<u8>,
+        sample_rate: usize,
+        /// 0 for mono/stereo, 1 for Vorbis layouts, 255 for discrete channels
+        mapping_family: u8,
         application: Application,
-        bitrate: usize,
+        frame_duration: FrameDuration,
+        /// Encoder controls, applied in order on configure()
+        ctls: Vec<(u32, u32)>,
+    }
+
+    impl Cfg {
+        fn is_valid(&self) -> bool {
+            match self.mapping_family {
+                0 => {
+                    self.channels > 0
+                        && self.streams + self.coupled_streams == self.channels
+                        && self.mapping.len() == self.channels
+                }
+                1 => self.channels > 0 && self.channels <= 8,
+                255 => self.channels > 0 && self.channels <= 255,
+                _ => false,
+            }
+        }
+
+        fn set_ctl(&mut self, key: u32, val: u32) {
+            if let Some(ctl) = self.ctls.iter_mut().find(|ctl| ctl.0 == key) {
+                ctl.1 = val;
+            } else {
+                self.ctls.push((key, val));
+            }
+        }
+    }
+
+    fn parse_bool(val: Value) -> Result<u32> {
+        match val {
+            Value::Bool(b) => Ok(b as u32),
+            Value::U64(v) if v <= 1 => Ok(v as u32),
+            _ => Err(Error::InvalidData),
+        }
     }
 
-    impl Cfg {
-        fn is_valid(&self) -> bool {
-            self.channels > 0
-                && self.streams + self.coupled_streams == self.channels
-                && self.mapping.len() == self.channels
+    fn parse_range(val: Value, min: u64, max: u64) -> Result<u32> {
+        match val {
+            Value::U64(v) if v >= min && v <= max => Ok(v as u32),
+            _ => Err(Error::InvalidData),
+        }
+    }
+
+    fn parse_bitrate(val: Value) -> Result<u32> {
+        match val {
+            Value::U64(v) if (500..=512000 * 255).contains(&v) => Ok(v as u32),
+            Value::Str("auto") => Ok(OPUS_AUTO as u32),
+            Value::Str("max") => Ok(OPUS_BITRATE_MAX as u32),
+            _ => Err(Error::InvalidData),
+        }
+    }
+
+    fn parse_bandwidth(val: Value) -> Result<Bandwidth> {
+        match val {
+            Value::Str(s) => s.parse().map_err(|_e| Error::InvalidData),
+            _ => Err(Error::InvalidData),
         }
     }
 
@@ -234,6 +1091,161 @@ mod encoder_trait {
         delay: usize,
         cfg: Cfg,
         flushing: bool,
+        /// Interleaved samples not yet forming a complete Opus frame
+        buffered: Samples,
+        /// Input channel index for each channel in the mapping family order
+        reorder: Option<Vec<usize>>,
+        /// Input channel layout, if provided through `set_params`
+        map: Option<ChannelMap>,
+        /// Timebase of the incoming frames, used for the packet timestamps
+        timebase: Rational64,
+        /// Timestamp of the first sample of the current contiguous run
+        start_pts: Option<i64>,
+        /// Samples already encoded since `start_pts`
+        emitted: i64,
+    }
+
+    impl Enc {
+        /// Converts a sample count at the input rate into the frame timebase.
+        fn samples_to_ts(&self, samples: i64) -> i64 {
+            (Rational64::new(samples, self.cfg.sample_rate as i64) / self.timebase).to_integer()
+        }
+
+        fn set_sample_rate(&mut self, sample_rate: usize) -> Result<()> {
+            match sample_rate {
+                8000 | 12000 | 16000 | 24000 | 48000 => {}
+                _ => return Err(Error::Unsupported("Unsupported sample rate".to_owned())),
+            }
+            if self.enc.is_some() && sample_rate != self.cfg.sample_rate {
+                return Err(Error::ConfigurationInvalid);
+            }
+
+            self.cfg.sample_rate = sample_rate;
+            self.frame_size = self.cfg.frame_duration.samples(sample_rate);
+
+            Ok(())
+        }
+
+        fn set_format(&mut self, format: &Soniton) -> Result<()> {
+            if *format != self.buffered.format() {
+                if !self.buffered.is_empty() {
+                    return Err(Error::ConfigurationInvalid);
+                }
+                self.buffered = parse_format(format)?;
+            }
+
+            Ok(())
+        }
+
+        fn set_frame_duration(&mut self, duration: &str) -> Result<()> {
+            let d: FrameDuration = duration.parse().map_err(|_e| Error::InvalidData)?;
+
+            if let Some(ref mut enc) = self.enc {
+                enc.set_option(OPUS_SET_EXPERT_FRAME_DURATION_REQUEST, d as u32)?;
+            }
+
+            self.cfg.frame_duration = d;
+            self.frame_size = d.samples(self.cfg.sample_rate);
+
+            Ok(())
+        }
+
+        /// Largest packet the encoder may produce for the current frame
+        /// duration, accounting for every stream.
+        fn max_packet_size(&self) -> usize {
+            let frames = self.cfg.frame_duration.samples(48000).div_ceil(960);
+
+            self.cfg.streams.max(1) * (MAX_HEADER_SIZE + frames.max(MAX_FRAMES) * MAX_FRAME_SIZE)
+        }
+
+        /// Encodes the first `frame_size` buffered samples and queues the
+        /// resulting packet, lasting `samples`.
+        ///
+        /// If less than a full frame is buffered the remainder is zero-padded.
+        fn encode_buffered(&mut self, samples: usize) -> Result<()> {
+            let max_packet_size = self.max_packet_size();
+            let enc = self.enc.as_mut().ok_or(Error::ConfigurationIncomplete)?;
+            let chunk_size = self.frame_size * self.cfg.channels;
+
+            self.buffered.pad(chunk_size);
+
+            let mut pkt = Packet::with_capacity(max_packet_size);
+            pkt.data.resize(max_packet_size, 0);
+
+            let len = enc.encode(self.buffered.as_buffer(chunk_size), pkt.data.as_mut_slice())?;
+            pkt.data.truncate(len);
+            self.buffered.consume(chunk_size);
+
+            let start_pts = self.start_pts.unwrap_or(0);
+            let start = self.samples_to_ts(self.emitted);
+            self.emitted += samples as i64;
+            let end = self.samples_to_ts(self.emitted);
+
+            pkt.t.pts = Some(start_pts + start);
+            pkt.t.dts = pkt.t.pts;
+            pkt.t.duration = Some((end - start) as u64);
+            pkt.t.timebase = Some(self.timebase);
+
+            self.pending.push_back(pkt);
+
+            Ok(())
+        }
+
+        /// Keeps track of the input timeline, starting a new contiguous run
+        /// when the frame timestamp does not follow the buffered samples.
+        fn update_timeline(&mut self, frame: &ArcFrame) -> Result<()> {
+            let pts = match (frame.t.pts, self.start_pts) {
+                (None, Some(_)) => return Ok(()),
+                (None, None) => 0,
+                (Some(pts), None) => pts,
+                (Some(pts), Some(start_pts)) => {
+                    let buffered = (self.buffered.len() / self.cfg.channels) as i64;
+                    let expected = start_pts + self.samples_to_ts(self.emitted + buffered);
+
+                    // Allow for the rounding to the frame timebase
+                    if (pts - expected).abs() <= 1 {
+                        return Ok(());
+                    }
+
+                    if !self.buffered.is_empty() {
+                        self.encode_buffered(buffered as usize)?;
+                    }
+                    pts
+                }
+            };
+
+            if let Some(timebase) = frame.t.timebase {
+                self.timebase = timebase;
+            }
+            self.start_pts = Some(pts);
+            self.emitted = 0;
+
+            Ok(())
+        }
+
+        /// Pushes out the buffered samples and the encoder lookahead.
+        ///
+        /// The last packet duration is shortened so the overall duration
+        /// matches the input plus the pre-skip, letting the muxer signal
+        /// the end trimming.
+        fn drain(&mut self) -> Result<()> {
+            let mut remaining = self.buffered.len() / self.cfg.channels + self.delay;
+
+            while remaining > 0 {
+                let samples = remaining.min(self.frame_size);
+                self.encode_buffered(samples)?;
+                remaining -= samples;
+            }
+
+            self.buffered.clear();
+            self.start_pts = None;
+            self.emitted = 0;
+            if let Some(enc) = self.enc.as_mut() {
+                enc.reset();
+            }
+
+            Ok(())
+        }
     }
 
     impl Descriptor for Des {
@@ -250,10 +1262,26 @@ mod encoder_trait {
                     streams: 0,
                     coupled_streams: 0,
                     mapping: vec![0, 1],
+                    sample_rate: 48000,
+                    mapping_family: 0,
                     application: Application::Audio,
-                    bitrate: 16000,
+                    frame_duration: FrameDuration::Ms20,
+                    ctls: vec![
+                        (OPUS_SET_BITRATE_REQUEST, 16000),
+                        (OPUS_SET_BANDWIDTH_REQUEST, OPUS_BANDWIDTH_WIDEBAND),
+                        (OPUS_SET_COMPLEXITY_REQUEST, 10),
+                        (OPUS_SET_VBR_REQUEST, 0),
+                        (OPUS_SET_VBR_CONSTRAINT_REQUEST, 0),
+                        (OPUS_SET_PACKET_LOSS_PERC_REQUEST, 0),
+                    ],
                 },
                 flushing: false,
+                buffered: Samples::I16(Vec::new()),
+                reorder: None,
+                map: None,
+                timebase: Rational64::new(1, 48000),
+                start_pts: None,
+                emitted: 0,
             }
         }
 
@@ -265,52 +1293,65 @@ mod encoder_trait {
     // Values copied from libopusenc.c
     // A packet may contain up to 3 frames, each of 1275 bytes max.
     // The packet header may be up to 7 bytes long.
+    // Frame durations above 60ms are packed as 20ms frames.
 
     const MAX_HEADER_SIZE: usize = 7;
     const MAX_FRAME_SIZE: usize = 1275;
     const MAX_FRAMES: usize = 3;
 
-    /// 80ms in samples
+    /// 80ms in samples at 48kHz
     const CONVERGENCE_WINDOW: usize = 3840;
 
     impl Encoder for Enc {
         fn configure(&mut self) -> Result<()> {
-            if self.enc.is_none() {
-                if self.cfg.is_valid() {
-                    let mut enc = OpusEncoder::create(
-                        48000, // TODO
-                        self.cfg.channels,
-                        self.cfg.streams,
-                        self.cfg.coupled_streams,
-                        &self.cfg.mapping,
-                        self.cfg.application,
-                    )
-                    .map_err(|_e| unimplemented!())?;
-                    enc.set_option(OPUS_SET_BITRATE_REQUEST, self.cfg.bitrate as u32)
-                        .unwrap();
-                    enc.set_option(OPUS_SET_BANDWIDTH_REQUEST, OPUS_BANDWIDTH_WIDEBAND)
-                        .unwrap();
-                    enc.set_option(OPUS_SET_COMPLEXITY_REQUEST, 10).unwrap();
-                    enc.set_option(OPUS_SET_VBR_REQUEST, 0).unwrap();
-                    enc.set_option(OPUS_SET_VBR_CONSTRAINT_REQUEST, 0).unwrap();
-                    enc.set_option(OPUS_SET_PACKET_LOSS_PERC_REQUEST, 0)
-                        .unwrap();
-
-                    self.delay = enc.get_option(OPUS_GET_LOOKAHEAD_REQUEST).unwrap() as usize;
-                    self.enc = Some(enc);
-                    Ok(())
-                } else {
-                    unimplemented!()
-                }
+            if self.enc.is_some() {
+                return Err(Error::ConfigurationInvalid);
+            }
+            if !self.cfg.is_valid() {
+                return Err(Error::ConfigurationIncomplete);
+            }
+
+            let mut enc = if self.cfg.mapping_family == 0 {
+                OpusEncoder::create(
+                    self.cfg.sample_rate,
+                    self.cfg.channels,
+                    self.cfg.streams,
+                    self.cfg.coupled_streams,
+                    &self.cfg.mapping,
+                    self.cfg.application,
+                )
             } else {
-                unimplemented!()
+                OpusEncoder::create_surround(
+                    self.cfg.sample_rate,
+                    self.cfg.channels,
+                    self.cfg.mapping_family,
+                    self.cfg.application,
+                )
+            }?;
+
+            self.cfg.streams = enc.streams();
+            self.cfg.coupled_streams = enc.coupled_streams();
+            self.cfg.mapping = enc.mapping().to_vec();
+
+            for &(key, val) in &self.cfg.ctls {
+                enc.set_option(key, val)?;
             }
+            enc.set_option(
+                OPUS_SET_EXPERT_FRAME_DURATION_REQUEST,
+                self.cfg.frame_duration as u32,
+            )?;
+
+            self.delay = enc.get_option(OPUS_GET_LOOKAHEAD_REQUEST)? as usize;
+            self.enc = Some(enc);
+
+            Ok(())
         }
-        // TODO: support multichannel
         fn get_extradata(&self) -> Option<Vec<u8>> {
             use bitstream::bytewrite::*;
-            if self.cfg.channels > 2 {
-                unimplemented!();
+
+            // The stream layout of the other families is picked by libopus
+            if self.cfg.mapping_family != 0 && self.enc.is_none() {
+                return None;
             }
 
             let mut buf = b"OpusHead".to_vec();
@@ -319,58 +1360,56 @@ mod encoder_trait {
 
             buf[8] = 1;
             buf[9] = self.cfg.channels as u8;
-            put_i16l(&mut buf[10..12], self.delay as i16);
-            put_i32l(&mut buf[12..16], 48000); // TODO
+            // The pre-skip is always expressed at 48kHz
+            let pre_skip = self.delay * 48000 / self.cfg.sample_rate;
+            put_i16l(&mut buf[10..12], pre_skip as i16);
+            put_i32l(&mut buf[12..16], self.cfg.sample_rate as i32);
             put_i16l(&mut buf[16..18], 0);
-            buf[18] = 0;
+            buf[18] = self.cfg.mapping_family;
+
+            if self.cfg.mapping_family != 0 {
+                buf.push(self.cfg.streams as u8);
+                buf.push(self.cfg.coupled_streams as u8);
+                buf.extend_from_slice(&self.cfg.mapping);
+            }
 
             Some(buf)
         }
 
         fn send_frame(&mut self, frame: &ArcFrame) -> Result<()> {
-            let enc = self.enc.as_mut().unwrap();
-            let pending = &mut self.pending;
+            if self.enc.is_none() {
+                return Err(Error::ConfigurationIncomplete);
+            }
+
             if let MediaKind::Audio(ref info) = frame.kind {
-                let channels = info.map.len();
-                let input_size = info.samples * channels;
-                let input: &[i16] = frame.buf.as_slice(0).unwrap();
-                let data_size = MAX_HEADER_SIZE + MAX_FRAMES * MAX_FRAME_SIZE;
-                let chunk_size = self.frame_size * channels;
-                let mut buf = Vec::with_capacity(chunk_size);
-                let mut pts = frame.t.pts.unwrap();
-
-                for chunk in input[..input_size].chunks(chunk_size) {
-                    let len = chunk.len();
-                    let mut pkt = Packet::with_capacity(data_size);
-
-                    pkt.data.resize(data_size, 0); // TODO is it needed?
-
-                    let input_data = if len < chunk_size {
-                        buf.clear();
-                        buf.extend_from_slice(chunk);
-                        buf.as_slice()
-                    } else {
-                        chunk
-                    };
+                let channels = self.cfg.channels;
+                if info.map.len() != channels {
+                    return Err(Error::InvalidData);
+                }
 
-                    match enc.encode(input_data, pkt.data.as_mut_slice()) {
-                        Ok(len) => {
-                            let duration = (Rational64::new(len as i64 / channels as i64, 48000)
-                                / frame.t.timebase.unwrap())
-                            .to_integer();
-                            pkt.t.pts = Some(pts);
-                            pkt.t.dts = Some(pts);
-                            pkt.t.duration = Some(duration as u64);
-                            pts += duration;
-                            pkt.data.truncate(len);
-                            pending.push_back(pkt);
-                        }
-                        Err(_) => unimplemented!(),
-                    }
+                if *info.format != self.buffered.format()
+                    || info.sample_rate != self.cfg.sample_rate
+                {
+                    return Err(Error::InvalidData);
+                }
+
+                self.update_timeline(frame)?;
+                self.flushing = false;
+
+                self.buffered.extend(
+                    frame,
+                    info.samples * channels,
+                    channels,
+                    self.reorder.as_deref(),
+                )?;
+
+                while self.buffered.len() >= self.frame_size * channels {
+                    self.encode_buffered(self.frame_size)?;
                 }
+
                 Ok(())
             } else {
-                unimplemented!() // TODO mark it unreachable?
+                Err(Error::InvalidData)
             }
         }
 
@@ -382,17 +1421,89 @@ mod encoder_trait {
             match (key, val) {
                 // ("format", Value::Formaton(f)) => self.format = Some(f),
                 // ("mapping", Value::ChannelMap(map) => self.cfg.map = map::to_vec()
-                ("channels", Value::U64(v)) => self.cfg.channels = v as usize,
+                ("channels" | "streams" | "coupled_streams" | "mapping_family", _)
+                    if self.enc.is_some() =>
+                {
+                    // The stream layout is fixed once the encoder is created
+                    return Err(Error::ConfigurationInvalid);
+                }
+                ("channels", Value::U64(v)) => {
+                    self.cfg.channels = v as usize;
+                    self.map = self.map.take().filter(|m| m.len() == v as usize);
+                }
                 ("streams", Value::U64(v)) => self.cfg.streams = v as usize,
                 ("coupled_streams", Value::U64(v)) => self.cfg.coupled_streams = v as usize,
+                ("format", Value::Str(s)) => {
+                    let format = match s {
+                        "s16" => S16,
+                        "f32" => F32,
+                        _ => return Err(Error::InvalidData),
+                    };
+                    self.set_format(&format)?;
+                }
+                ("sample_rate", Value::U64(v)) => self.set_sample_rate(v as usize)?,
+                ("mapping_family", Value::U64(v)) => match v {
+                    0 | 1 | 255 => self.cfg.mapping_family = v as u8,
+                    _ => return Err(Error::InvalidData),
+                },
                 ("application", Value::Str(s)) => {
-                    if let Ok(a) = s.parse() {
-                        self.cfg.application = a;
-                    } else {
-                        return Err(Error::InvalidData);
+                    let a = s.parse().map_err(|_e| Error::InvalidData)?;
+                    if let Some(ref mut enc) = self.enc {
+                        // The restricted low delay mode changes the codec
+                        // delay, it can be picked only before configure().
+                        match (self.cfg.application, a) {
+                            (Application::LowDelay, _) | (_, Application::LowDelay) => {
+                                return Err(Error::ConfigurationInvalid)
+                            }
+                            _ => enc.set_option(OPUS_SET_APPLICATION_REQUEST, a as u32)?,
+                        }
+                    }
+                    self.cfg.application = a;
+                }
+                ("frame_duration", Value::Str(s)) => self.set_frame_duration(s)?,
+                ("frame_duration", Value::U64(v)) => self.set_frame_duration(&v.to_string())?,
+                (key, val) => {
+                    let ctl = match key {
+                        "bitrate" => (OPUS_SET_BITRATE_REQUEST, parse_bitrate(val)?),
+                        "complexity" => (OPUS_SET_COMPLEXITY_REQUEST, parse_range(val, 0, 10)?),
+                        "vbr" => (OPUS_SET_VBR_REQUEST, parse_bool(val)?),
+                        "vbr_constraint" => (OPUS_SET_VBR_CONSTRAINT_REQUEST, parse_bool(val)?),
+                        "bandwidth" => (OPUS_SET_BANDWIDTH_REQUEST, parse_bandwidth(val)? as u32),
+                        "max_bandwidth" => match parse_bandwidth(val)? {
+                            Bandwidth::Auto => return Err(Error::InvalidData),
+                            b => (OPUS_SET_MAX_BANDWIDTH_REQUEST, b as u32),
+                        },
+                        "signal" => match val {
+                            Value::Str(s) => {
+                                let signal: Signal = s.parse().map_err(|_e| Error::InvalidData)?;
+                                (OPUS_SET_SIGNAL_REQUEST, signal as u32)
+                            }
+                            _ => return Err(Error::InvalidData),
+                        },
+                        "fec" => (OPUS_SET_INBAND_FEC_REQUEST, parse_bool(val)?),
+                        "packet_loss" => {
+                            (OPUS_SET_PACKET_LOSS_PERC_REQUEST, parse_range(val, 0, 100)?)
+                        }
+                        "dtx" => (OPUS_SET_DTX_REQUEST, parse_bool(val)?),
+                        "lsb_depth" => (OPUS_SET_LSB_DEPTH_REQUEST, parse_range(val, 8, 24)?),
+                        "prediction_disabled" => {
+                            (OPUS_SET_PREDICTION_DISABLED_REQUEST, parse_bool(val)?)
+                        }
+                        "phase_inversion_disabled" => {
+                            (OPUS_SET_PHASE_INVERSION_DISABLED_REQUEST, parse_bool(val)?)
+                        }
+                        #[cfg(feature = "dred")]
+                        "dred_duration" => {
+                            (OPUS_SET_DRED_DURATION_REQUEST, parse_range(val, 0, 104)?)
+                        }
+                        _ => return Err(Error::Unsupported("Unsupported option".to_owned())),
+                    };
+
+                    if let Some(ref mut enc) = self.enc {
+                        enc.set_option(ctl.0, ctl.1)?;
                     }
+                    self.cfg.set_ctl(ctl.0, ctl.1);
                 }
-                _ => return Err(Error::Unsupported("Unsupported option".to_owned())),
             }
 
             Ok(())
@@ -401,42 +1512,80 @@ mod encoder_trait {
         fn set_params(&mut self, params: &CodecParams) -> Result<()> {
             use data::params::*;
             if let Some(MediaKind::Audio(ref info)) = params.kind {
+                self.set_sample_rate(info.rate)?;
+                if let Some(ref format) = info.format {
+                    self.set_format(format)?;
+                }
                 if let Some(ref map) = info.map {
+                    if map.is_empty() {
+                        return Err(Error::ConfigurationInvalid);
+                    }
+                    if map.len() > 255 {
+                        return Err(Error::Unsupported("Too many channels".to_owned()));
+                    }
+                    self.map = Some(map.clone());
                     if map.len() > 2 {
-                        unimplemented!()
+                        self.cfg.channels = map.len();
+                        // Layouts not matching a Vorbis one are coded as
+                        // discrete channels.
+                        let order = vorbis_order(map);
+                        self.cfg.mapping_family = if order.is_some() { 1 } else { 255 };
+                        self.reorder = order.filter(|o| o.iter().enumerate().any(|(i, &c)| i != c));
                     } else {
                         self.cfg.channels = map.len();
                         self.cfg.coupled_streams = self.cfg.channels - 1;
                         self.cfg.streams = 1;
                         self.cfg.mapping = if map.len() > 1 { vec![0, 1] } else { vec![0] };
+                        self.cfg.mapping_family = 0;
+                        self.reorder = None;
                     }
                 }
             }
             Ok(())
         }
 
-        // TODO: guard against calling it before configure()
-        // is issued.
         fn get_params(&self) -> Result<CodecParams> {
             use data::params::*;
             use std::sync::Arc;
+
+            let enc = self.enc.as_ref().ok_or(Error::ConfigurationIncomplete)?;
+            // The sum of the stream allocations libopus settled on, what the
+            // automatic and maximum settings resolve to included.
+            let bit_rate = enc.get_option(OPUS_GET_BITRATE_REQUEST)?;
+
+            // Discrete channels have no position unless the caller gave one
+            let map = self.map.clone().or_else(|| {
+                if self.cfg.mapping_family != 255 {
+                    vorbis_map(self.cfg.channels)
+                } else {
+                    None
+                }
+            });
+
             Ok(CodecParams {
                 kind: Some(MediaKind::Audio(AudioInfo {
-                    rate: 48000,
-                    map: Some(ChannelMap::default_map(2)),
-                    format: Some(Arc::new(S16)),
+                    rate: self.cfg.sample_rate,
+                    map,
+                    format: Some(Arc::new(self.buffered.format())),
                 })),
                 codec_id: Some("opus".to_owned()),
                 extradata: self.get_extradata(),
-                bit_rate: 0, // TODO: expose the information
-                convergence_window: CONVERGENCE_WINDOW,
+                bit_rate: bit_rate as usize,
+                convergence_window: CONVERGENCE_WINDOW * self.cfg.sample_rate / 48000,
                 delay: self.delay,
             })
         }
 
         fn flush(&mut self) -> Result<()> {
-            // unimplemented!()
+            if self.enc.is_none() {
+                return Err(Error::ConfigurationIncomplete);
+            }
+
+            if !self.flushing && self.start_pts.is_some() {
+                self.drain()?;
+            }
             self.flushing = true;
+
             Ok(())
         }
     }
@@ -448,8 +1597,535 @@ mod encoder_trait {
             desc: "libopus encoder",
             mime: "audio/OPUS",
         },
+        formats: &[S16, F32],
     };
+
+    #[cfg(test)]
+    pub(crate) mod tests {
+        use super::*;
+        use crate::common::available;
+        use bitstream::byteread::{get_i16l, get_i32l};
+        use data::frame::{AudioInfo, Frame};
+        use data::timeinfo::TimeInfo;
+        use std::sync::Arc;
+
+        pub(crate) fn stereo_frame(samples: usize, pts: i64) -> ArcFrame {
+            let info = AudioInfo {
+                samples,
+                sample_rate: 48000,
+                map: ChannelMap::default_map(2),
+                format: Arc::new(S16),
+                block_len: None,
+            };
+            let t = TimeInfo {
+                pts: Some(pts),
+                timebase: Some(Rational64::new(1, 48000)),
+                ..Default::default()
+            };
+
+            Arc::new(Frame::new_default_frame(info, Some(t)))
+        }
+
+        pub(crate) fn stereo_encoder() -> Enc {
+            let mut enc = OPUS_DESCR.create();
+            enc.set_option("channels", Value::U64(2)).unwrap();
+            enc.set_option("streams", Value::U64(1)).unwrap();
+            enc.set_option("coupled_streams", Value::U64(1)).unwrap();
+            enc.configure().unwrap();
+            enc
+        }
+
+        /// Sends `frame` and collects the packets the encoder completes.
+        pub(crate) fn encode(enc: &mut Enc, frame: &ArcFrame) -> Vec<Packet> {
+            enc.send_frame(frame).unwrap();
+            let mut packets = Vec::new();
+            while let Ok(pkt) = enc.receive_packet() {
+                packets.push(pkt);
+            }
+            packets
+        }
+
+        #[test]
+        fn accumulate_frames() {
+            if !available() {
+                return;
+            }
+            let mut enc = stereo_encoder();
+
+            for i in 0..5 {
+                enc.send_frame(&stereo_frame(441, i * 441)).unwrap();
+            }
+
+            for pts in &[0, 960] {
+                let pkt = enc.receive_packet().unwrap();
+                assert_eq!(pkt.t.pts, Some(*pts));
+                assert_eq!(pkt.t.duration, Some(960));
+            }
+            assert!(enc.receive_packet().is_err());
+
+            // A gap in the input timeline pushes out the buffered samples.
+            enc.send_frame(&stereo_frame(960, 48000)).unwrap();
+
+            let pkt = enc.receive_packet().unwrap();
+            assert_eq!(pkt.t.pts, Some(1920));
+            assert_eq!(pkt.t.duration, Some(2205 - 1920));
+
+            let pkt = enc.receive_packet().unwrap();
+            assert_eq!(pkt.t.pts, Some(48000));
+            assert_eq!(pkt.t.duration, Some(960));
+        }
+
+        #[test]
+        fn flush_drains_lookahead() {
+            if !available() {
+                return;
+            }
+            let mut enc = stereo_encoder();
+
+            enc.send_frame(&stereo_frame(1000, 0)).unwrap();
+            enc.flush().unwrap();
+
+            let mut duration = 0;
+            while let Ok(pkt) = enc.receive_packet() {
+                assert_eq!(pkt.t.pts, Some(duration as i64));
+                duration += pkt.t.duration.unwrap();
+            }
+
+            assert_eq!(duration as usize, 1000 + enc.delay);
+
+            enc.flush().unwrap();
+            assert!(enc.receive_packet().is_err());
+        }
+
+        #[test]
+        fn float_roundtrip() {
+            if !available() {
+                return;
+            }
+            use codec::decoder::{Decoder, Descriptor as _};
+
+            let mut enc = OPUS_DESCR.create();
+            enc.set_option("channels", Value::U64(2)).unwrap();
+            enc.set_option("streams", Value::U64(1)).unwrap();
+            enc.set_option("coupled_streams", Value::U64(1)).unwrap();
+            enc.set_option("format", Value::Str("f32")).unwrap();
+            enc.configure().unwrap();
+
+            // 16 bit frames are rejected once float input is selected
+            assert!(enc.send_frame(&stereo_frame(960, 0)).is_err());
+
+            let info = AudioInfo {
+                samples: 960,
+                sample_rate: 48000,
+                map: ChannelMap::default_map(2),
+                format: Arc::new(F32),
+                block_len: None,
+            };
+            let frame = Frame::new_default_frame(info, Some(TimeInfo::default()));
+            enc.send_frame(&Arc::new(frame)).unwrap();
+            let pkt = enc.receive_packet().unwrap();
+
+            let mut dec = crate::decoder::OPUS_DESCR.create();
+            dec.set_option("format", Value::Str("f32")).unwrap();
+            dec.set_extradata(&enc.get_extradata().unwrap());
+            dec.configure().unwrap();
+            dec.send_packet(&pkt).unwrap();
+
+            let frame = dec.receive_frame().unwrap();
+            if let data::frame::MediaKind::Audio(ref info) = frame.kind {
+                assert_eq!(*info.format, F32);
+                // The first packet loses the pre-skip
+                assert_eq!(info.samples, 960 - enc.delay);
+            } else {
+                unreachable!();
+            }
+        }
+
+        #[test]
+        fn narrowband_input() {
+            if !available() {
+                return;
+            }
+            use codec::decoder::{Decoder, Descriptor as _};
+
+            let mut enc = OPUS_DESCR.create();
+            enc.set_option("channels", Value::U64(2)).unwrap();
+            enc.set_option("streams", Value::U64(1)).unwrap();
+            enc.set_option("coupled_streams", Value::U64(1)).unwrap();
+            enc.set_option("sample_rate", Value::U64(8000)).unwrap();
+            assert!(enc.set_option("sample_rate", Value::U64(44100)).is_err());
+            enc.configure().unwrap();
+
+            let extradata = enc.get_extradata().unwrap();
+            assert_eq!(get_i16l(&extradata[10..12]) as usize, enc.delay * 6);
+            assert_eq!(get_i32l(&extradata[12..16]), 8000);
+
+            let info = AudioInfo {
+                samples: 320,
+                sample_rate: 8000,
+                map: ChannelMap::default_map(2),
+                format: Arc::new(S16),
+                block_len: None,
+            };
+            let t = TimeInfo {
+                pts: Some(0),
+                timebase: Some(Rational64::new(1, 1000)),
+                ..Default::default()
+            };
+            let frame = Frame::new_default_frame(info, Some(t));
+            enc.send_frame(&Arc::new(frame)).unwrap();
+
+            let mut dec = crate::decoder::OPUS_DESCR.create();
+            dec.set_option("sample_rate", Value::U64(8000)).unwrap();
+            dec.set_extradata(&extradata);
+            dec.configure().unwrap();
+
+            for pts in &[0, 20] {
+                let pkt = enc.receive_packet().unwrap();
+                assert_eq!(pkt.t.pts, Some(*pts));
+                assert_eq!(pkt.t.duration, Some(20));
+
+                dec.send_packet(&pkt).unwrap();
+                let frame = dec.receive_frame().unwrap();
+                if let data::frame::MediaKind::Audio(ref info) = frame.kind {
+                    assert_eq!(info.sample_rate, 8000);
+                    let skip = if *pts == 0 { enc.delay } else { 0 };
+                    assert_eq!(info.samples, 160 - skip);
+                } else {
+                    unreachable!();
+                }
+            }
+        }
+
+        #[test]
+        fn reported_params() {
+            if !available() {
+                return;
+            }
+            use data::audiosample::ChannelType::*;
+            use data::params::{AudioInfo, MediaKind};
+
+            let mut map = ChannelMap::new();
+            map.add_channels(&[L, R, C, LFE, Ls, Rs]);
+
+            let mut enc = OPUS_DESCR.create();
+            enc.set_params(&CodecParams {
+                kind: Some(MediaKind::Audio(AudioInfo {
+                    rate: 16000,
+                    map: Some(map.clone()),
+                    format: Some(Arc::new(F32)),
+                })),
+                codec_id: None,
+                extradata: None,
+                bit_rate: 0,
+                convergence_window: 0,
+                delay: 0,
+            })
+            .unwrap();
+            enc.set_option("bitrate", Value::U64(96000)).unwrap();
+
+            assert!(enc.get_params().is_err());
+            enc.configure().unwrap();
+
+            assert!(enc.get_params().unwrap().bit_rate > 0);
+
+            // The streams share the target once a frame is encoded
+            let info = data::frame::AudioInfo {
+                samples: 320,
+                sample_rate: 16000,
+                map,
+                format: Arc::new(F32),
+                block_len: None,
+            };
+            let frame = Frame::new_default_frame(info, Some(TimeInfo::default()));
+            enc.send_frame(&Arc::new(frame)).unwrap();
+            let bit_rate = enc.get_params().unwrap().bit_rate;
+            assert!(bit_rate > 0 && bit_rate <= 96000);
+
+            enc.set_option("bitrate", Value::Str("auto")).unwrap();
+            assert!(enc.get_params().unwrap().bit_rate > 0);
+
+            let params = enc.get_params().unwrap();
+            assert_eq!(params.convergence_window, 1280);
+            assert_eq!(params.delay, enc.delay);
+            if let Some(MediaKind::Audio(info)) = params.kind {
+                assert_eq!(info.rate, 16000);
+                assert_eq!(*info.format.unwrap(), F32);
+                let map = info.map.unwrap();
+                assert_eq!(map.len(), 6);
+                assert_eq!(map.get_channel(3), LFE);
+            } else {
+                unreachable!();
+            }
+
+            let mut enc = OPUS_DESCR.create();
+            let empty = CodecParams {
+                kind: Some(MediaKind::Audio(AudioInfo {
+                    rate: 48000,
+                    map: Some(ChannelMap::new()),
+                    format: None,
+                })),
+                codec_id: None,
+                extradata: None,
+                bit_rate: 0,
+                convergence_window: 0,
+                delay: 0,
+            };
+            assert!(enc.set_params(&empty).is_err());
+        }
+
+        #[test]
+        fn error_mapping() {
+            use crate::common::{Error as OpusError, Operation};
+            use ffi::{
+                OPUS_BAD_ARG, OPUS_BUFFER_TOO_SMALL, OPUS_SET_BITRATE_REQUEST, OPUS_UNIMPLEMENTED,
+            };
+
+            let e = OpusError::opus(Operation::Encode, OPUS_BUFFER_TOO_SMALL);
+            match Error::from(e) {
+                Error::InvalidData => {}
+                _ => panic!("encoding errors are data errors"),
+            }
+
+            let e = OpusError::opus(
+                Operation::StreamCtl(OPUS_SET_BITRATE_REQUEST, 1),
+                OPUS_BAD_ARG,
+            );
+            assert_eq!(
+                e.to_string().split(':').next(),
+                Some("ctl request 4002 on stream 1")
+            );
+            match Error::from(e) {
+                Error::ConfigurationInvalid => {}
+                _ => panic!("ctl errors are configuration errors"),
+            }
+
+            let e = OpusError::opus(Operation::Ctl(OPUS_SET_BITRATE_REQUEST), OPUS_UNIMPLEMENTED);
+            match Error::from(e) {
+                Error::Unsupported(s) => assert!(s.starts_with("ctl request 4002")),
+                _ => panic!("unimplemented requests are unsupported"),
+            }
+        }
+
+        #[test]
+        fn tuning_options() {
+            if !available() {
+                return;
+            }
+            let mut enc = OPUS_DESCR.create();
+            enc.set_option("channels", Value::U64(2)).unwrap();
+            enc.set_option("streams", Value::U64(1)).unwrap();
+            enc.set_option("coupled_streams", Value::U64(1)).unwrap();
+            enc.set_option("bitrate", Value::U64(64000)).unwrap();
+            enc.set_option("bandwidth", Value::Str("fullband")).unwrap();
+            enc.set_option("vbr", Value::Bool(true)).unwrap();
+
+            assert!(enc.set_option("complexity", Value::U64(11)).is_err());
+            assert!(enc.set_option("max_bandwidth", Value::Str("auto")).is_err());
+            assert!(enc.set_option("signal", Value::Str("noise")).is_err());
+
+            enc.configure().unwrap();
+
+            assert!(enc.set_option("channels", Value::U64(1)).is_err());
+            assert!(enc.set_option("mapping_family", Value::U64(1)).is_err());
+
+            enc.set_option("packet_loss", Value::U64(10)).unwrap();
+            enc.set_option("fec", Value::Bool(true)).unwrap();
+            enc.set_option("signal", Value::Str("voice")).unwrap();
+            enc.set_option("phase_inversion_disabled", Value::U64(1))
+                .unwrap();
+            assert!(enc.set_option("lsb_depth", Value::U64(32)).is_err());
+        }
+
+        #[test]
+        fn low_delay_frames() {
+            if !available() {
+                return;
+            }
+            let mut enc = OPUS_DESCR.create();
+            enc.set_option("channels", Value::U64(2)).unwrap();
+            enc.set_option("streams", Value::U64(1)).unwrap();
+            enc.set_option("coupled_streams", Value::U64(1)).unwrap();
+            enc.set_option("application", Value::Str("lowdelay"))
+                .unwrap();
+            enc.set_option("frame_duration", Value::Str("2.5")).unwrap();
+            enc.configure().unwrap();
+
+            assert!(enc.set_option("application", Value::Str("audio")).is_err());
+
+            enc.send_frame(&stereo_frame(480, 0)).unwrap();
+            for i in 0..4 {
+                let pkt = enc.receive_packet().unwrap();
+                assert_eq!(pkt.t.pts, Some(i * 120));
+                assert_eq!(pkt.t.duration, Some(120));
+            }
+
+            enc.set_option("frame_duration", Value::U64(10)).unwrap();
+            enc.send_frame(&stereo_frame(480, 480)).unwrap();
+            let pkt = enc.receive_packet().unwrap();
+            assert_eq!(pkt.t.pts, Some(480));
+            assert_eq!(pkt.t.duration, Some(480));
+        }
+    }
 }
 
 #[cfg(feature = "codec-trait")]
 pub use self::encoder_trait::OPUS_DESCR;
+
+#[cfg(all(test, feature = "codec-trait"))]
+pub(crate) use self::encoder_trait::tests as trait_tests;
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::mem::MaybeUninit;
+
+    /// 20ms of stereo input at 48kHz.
+    pub(crate) fn stereo_input() -> Vec<i16> {
+        (0..1920).map(|i| ((i * 37) % 1024) as i16).collect()
+    }
+
+    pub(crate) fn stereo_encoder() -> Encoder {
+        Encoder::create(48000, 2, 1, 1, &[0, 1], Application::Audio).unwrap()
+    }
+
+    /// Encodes `input` into a packet of its own.
+    pub(crate) fn encode<'a, I>(enc: &mut Encoder, input: I) -> Vec<u8>
+    where
+        I: Into<AudioBuffer<'a>>,
+    {
+        let mut pkt = vec![0u8; 4000];
+        let len = enc.encode(input, &mut pkt).unwrap();
+        pkt.truncate(len);
+        pkt
+    }
+
+    #[test]
+    fn cloned_state() {
+        if !available() {
+            return;
+        }
+        let mut enc = stereo_encoder();
+        let input = stereo_input();
+        encode(&mut enc, &input[..]);
+
+        let mut copy = enc.try_clone().unwrap();
+        assert_eq!(encode(&mut enc, &input[..]), encode(&mut copy, &input[..]));
+
+        let enc = Encoder::create(16000, 1, 1, 0, &[0], Application::Voip).unwrap();
+        let copy = enc.try_clone().unwrap();
+        assert_eq!(copy.sample_rate(), 16000);
+        assert_eq!(
+            copy.get_option(OPUS_GET_SAMPLE_RATE_REQUEST).unwrap(),
+            16000
+        );
+    }
+
+    #[test]
+    fn pooled_states() {
+        if !available() {
+            return;
+        }
+        let mem = StateMemory::new(16);
+        assert!(Encoder::create_in(mem, 48000, 2, 1, 1, &[0, 1], Application::Audio).is_err());
+        assert!(Encoder::state_size(0, 0).is_err());
+        assert!(Encoder::state_size(1, 1).unwrap() > 16);
+
+        let layout = EncoderLayout {
+            sample_rate: 48000,
+            channels: 2,
+            streams: 1,
+            coupled_streams: 1,
+            mapping: vec![0, 1],
+            application: Application::Audio,
+        };
+        let mut pool = EncoderPool::new(layout.clone(), 2).unwrap();
+        let input = stereo_input();
+
+        let mut enc = pool.get().unwrap();
+        assert_eq!(pool.available(), 1);
+        let first = encode(&mut enc, &input[..]);
+        assert_ne!(encode(&mut enc, &input[..]), first);
+
+        // Returned states are as good as new
+        pool.put(enc);
+        assert_eq!(pool.available(), 2);
+        assert_eq!(encode(&mut pool.get().unwrap(), &input[..]), first);
+
+        // Layouts not matching the pool are dropped
+        pool.put(Encoder::create_surround(48000, 2, 0, Application::Audio).unwrap());
+        assert_eq!(pool.available(), 1);
+
+        // Caller memory, handed over to the states
+        let size = Encoder::state_size(1, 1).unwrap();
+        let memory = (0..2)
+            .map(|_| {
+                let buf = vec![MaybeUninit::uninit(); size].into_boxed_slice();
+                StateMemory::from_boxed(buf).unwrap()
+            })
+            .collect::<Vec<_>>();
+        let mut pool = EncoderPool::with_memory(layout, memory).unwrap();
+        assert_eq!(pool.available(), 2);
+        assert_eq!(encode(&mut pool.get().unwrap(), &input[..]), first);
+    }
+
+    #[test]
+    fn error_context() {
+        if !available() {
+            return;
+        }
+        match Encoder::create(48000, 2, 1, 1, &[0], Application::Audio) {
+            Err(Error::Invalid { op, .. }) => assert_eq!(op, Operation::Create),
+            _ => panic!("short mapping accepted"),
+        }
+
+        let mut enc = stereo_encoder();
+        let e = enc.set_option(OPUS_SET_COMPLEXITY_REQUEST, 11).unwrap_err();
+        assert_eq!(e.operation(), Operation::Ctl(OPUS_SET_COMPLEXITY_REQUEST));
+        assert_eq!(e.raw_code(), Some(ErrorCode::BadArg as i32));
+
+        let e = enc.encode(&stereo_input()[..], &mut []).unwrap_err();
+        assert_eq!(e.operation(), Operation::Encode);
+        assert!(matches!(
+            e.code(),
+            ErrorCode::BadArg | ErrorCode::BufferTooSmall
+        ));
+        assert!(e.to_string().starts_with("encode: "));
+    }
+
+    #[cfg(feature = "dred")]
+    #[test]
+    fn dred_duration() {
+        if !available() {
+            return;
+        }
+        let mut enc = Encoder::create(48000, 2, 1, 1, &[0, 1], Application::Voip).unwrap();
+        enc.set_dred_duration(50).unwrap();
+        assert_eq!(enc.dred_duration().unwrap(), 50);
+    }
+
+    #[test]
+    fn planar_input() {
+        if !available() {
+            return;
+        }
+        let left: Vec<f32> = (0..960).map(|i| (i as f32 * 0.05).sin() * 0.5).collect();
+        let right: Vec<f32> = (0..960).map(|i| (i as f32 * 0.07).cos() * 0.25).collect();
+        let interleaved: Vec<f32> = left
+            .iter()
+            .zip(&right)
+            .flat_map(|(&l, &r)| vec![l, r])
+            .collect();
+
+        let mut enc = stereo_encoder();
+        let mut copy = enc.try_clone().unwrap();
+        let planar = encode(&mut enc, &[&left[..], &right[..]][..]);
+        assert_eq!(planar, encode(&mut copy, &interleaved[..]));
+
+        let mut pkt = [0u8; 4000];
+        assert!(enc
+            .encode(&[&left[..], &right[..959]][..], &mut pkt[..])
+            .is_err());
+        assert!(enc.encode(&[&left[..]][..], &mut pkt[..]).is_err());
+    }
+}
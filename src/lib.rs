@@ -1,14 +1,25 @@
 extern crate opus_sys as ffi;
 
-#[cfg(feature="codec-trait")]
+#[cfg(feature = "codec-trait")]
 extern crate av_data as data;
 
-#[cfg(feature="codec-trait")]
+#[cfg(feature = "codec-trait")]
 extern crate av_codec as codec;
 
-#[cfg(feature="codec-trait")]
+#[cfg(feature = "codec-trait")]
 extern crate av_bitstream as bitstream;
 
 pub mod common;
-pub mod encoder;
+#[cfg(feature = "custom-modes")]
+pub mod custom;
 pub mod decoder;
+#[cfg(feature = "dred")]
+pub mod dred;
+pub mod encoder;
+mod planar;
+
+pub use common::{supports, version, Capability, Version};
+
+/// Opening libopus at runtime, see `opus_sys::load` for how it is located
+#[cfg(feature = "dlopen")]
+pub use ffi::{library, load, Library, LoadError};
@@ -0,0 +1,232 @@
+//! Conversions between planar and interleaved samples.
+//!
+//! Stereo, the common case, uses SSE2 on x86_64 for both sample formats.
+//! Other targets and channel counts go through the scalar loops, which
+//! the compiler is free to vectorize on its own.
+
+pub(crate) trait Sample: Copy + Default {
+    fn scratch(scratch: &mut Scratch) -> &mut Vec<Self>;
+
+    fn interleave_stereo(left: &[Self], right: &[Self], out: &mut [Self]) {
+        for ((o, &l), &r) in out.chunks_exact_mut(2).zip(left).zip(right) {
+            o[0] = l;
+            o[1] = r;
+        }
+    }
+
+    fn deinterleave_stereo(samples: &[Self], left: &mut [Self], right: &mut [Self]) {
+        for ((s, l), r) in samples.chunks_exact(2).zip(left).zip(right) {
+            *l = s[0];
+            *r = s[1];
+        }
+    }
+}
+
+impl Sample for i16 {
+    fn scratch(scratch: &mut Scratch) -> &mut Vec<i16> {
+        &mut scratch.i16
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    fn interleave_stereo(left: &[i16], right: &[i16], out: &mut [i16]) {
+        use std::arch::x86_64::*;
+
+        let len = left.len().min(right.len()).min(out.len() / 2);
+        let simd = len / 8 * 8;
+        // SSE2 is part of the x86_64 baseline
+        unsafe {
+            for i in (0..simd).step_by(8) {
+                let l = _mm_loadu_si128(left.as_ptr().add(i) as *const __m128i);
+                let r = _mm_loadu_si128(right.as_ptr().add(i) as *const __m128i);
+                let o = out.as_mut_ptr().add(2 * i) as *mut __m128i;
+                _mm_storeu_si128(o, _mm_unpacklo_epi16(l, r));
+                _mm_storeu_si128(o.add(1), _mm_unpackhi_epi16(l, r));
+            }
+        }
+        for i in simd..len {
+            out[2 * i] = left[i];
+            out[2 * i + 1] = right[i];
+        }
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    fn deinterleave_stereo(samples: &[i16], left: &mut [i16], right: &mut [i16]) {
+        use std::arch::x86_64::*;
+
+        let len = left.len().min(right.len()).min(samples.len() / 2);
+        let simd = len / 8 * 8;
+        unsafe {
+            for i in (0..simd).step_by(8) {
+                let a = _mm_loadu_si128(samples.as_ptr().add(2 * i) as *const __m128i);
+                let b = _mm_loadu_si128(samples.as_ptr().add(2 * i + 8) as *const __m128i);
+                // Sign extend either half of every pair, packing back is exact
+                let l = _mm_packs_epi32(
+                    _mm_srai_epi32(_mm_slli_epi32(a, 16), 16),
+                    _mm_srai_epi32(_mm_slli_epi32(b, 16), 16),
+                );
+                let r = _mm_packs_epi32(_mm_srai_epi32(a, 16), _mm_srai_epi32(b, 16));
+                _mm_storeu_si128(left.as_mut_ptr().add(i) as *mut __m128i, l);
+                _mm_storeu_si128(right.as_mut_ptr().add(i) as *mut __m128i, r);
+            }
+        }
+        for i in simd..len {
+            left[i] = samples[2 * i];
+            right[i] = samples[2 * i + 1];
+        }
+    }
+}
+
+impl Sample for f32 {
+    fn scratch(scratch: &mut Scratch) -> &mut Vec<f32> {
+        &mut scratch.f32
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    fn interleave_stereo(left: &[f32], right: &[f32], out: &mut [f32]) {
+        use std::arch::x86_64::*;
+
+        let len = left.len().min(right.len()).min(out.len() / 2);
+        let simd = len / 4 * 4;
+        unsafe {
+            for i in (0..simd).step_by(4) {
+                let l = _mm_loadu_ps(left.as_ptr().add(i));
+                let r = _mm_loadu_ps(right.as_ptr().add(i));
+                let o = out.as_mut_ptr().add(2 * i);
+                _mm_storeu_ps(o, _mm_unpacklo_ps(l, r));
+                _mm_storeu_ps(o.add(4), _mm_unpackhi_ps(l, r));
+            }
+        }
+        for i in simd..len {
+            out[2 * i] = left[i];
+            out[2 * i + 1] = right[i];
+        }
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    fn deinterleave_stereo(samples: &[f32], left: &mut [f32], right: &mut [f32]) {
+        use std::arch::x86_64::*;
+
+        let len = left.len().min(right.len()).min(samples.len() / 2);
+        let simd = len / 4 * 4;
+        unsafe {
+            for i in (0..simd).step_by(4) {
+                let a = _mm_loadu_ps(samples.as_ptr().add(2 * i));
+                let b = _mm_loadu_ps(samples.as_ptr().add(2 * i + 4));
+                _mm_storeu_ps(
+                    left.as_mut_ptr().add(i),
+                    _mm_shuffle_ps(a, b, 0b10_00_10_00),
+                );
+                _mm_storeu_ps(
+                    right.as_mut_ptr().add(i),
+                    _mm_shuffle_ps(a, b, 0b11_01_11_01),
+                );
+            }
+        }
+        for i in simd..len {
+            left[i] = samples[2 * i];
+            right[i] = samples[2 * i + 1];
+        }
+    }
+}
+
+/// Interleaved samples standing in for planar buffers, kept by the
+/// encoders and decoders so converting does not allocate on every call.
+#[derive(Default)]
+pub(crate) struct Scratch {
+    f32: Vec<f32>,
+    i16: Vec<i16>,
+}
+
+impl Scratch {
+    /// A buffer of `len` samples, with whatever the last call left in it.
+    pub(crate) fn get<T: Sample>(&mut self, len: usize) -> &mut [T] {
+        let buf = T::scratch(self);
+        if buf.len() < len {
+            buf.resize(len, T::default());
+        }
+        &mut buf[..len]
+    }
+}
+
+/// Interleaves `planes` into `out`, which holds a sample of every plane
+/// per frame.
+pub(crate) fn interleave<T: Sample>(planes: &[&[T]], out: &mut [T]) {
+    match planes {
+        [mono] => out.copy_from_slice(mono),
+        [left, right] => T::interleave_stereo(left, right, out),
+        _ => {
+            let channels = planes.len();
+            for (c, plane) in planes.iter().enumerate() {
+                for (o, &s) in out.iter_mut().skip(c).step_by(channels).zip(plane.iter()) {
+                    *o = s;
+                }
+            }
+        }
+    }
+}
+
+/// Splits the interleaved `samples` into `planes`, as many frames as
+/// `samples` holds.
+pub(crate) fn deinterleave<T: Sample>(samples: &[T], planes: &mut [&mut [T]]) {
+    match planes {
+        [mono] => {
+            let len = samples.len();
+            mono[..len].copy_from_slice(samples)
+        }
+        [left, right] => T::deinterleave_stereo(samples, left, right),
+        _ => {
+            let channels = planes.len();
+            for (c, plane) in planes.iter_mut().enumerate() {
+                for (p, &s) in plane
+                    .iter_mut()
+                    .zip(samples.iter().skip(c).step_by(channels))
+                {
+                    *p = s;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `channels` planes of 13 samples, odd to go through the SIMD remainder.
+    fn planes(channels: i16) -> Vec<Vec<i16>> {
+        (0..channels)
+            .map(|c| (0..13).map(|i| (c * 100 + i) * (i % 3 - 1)).collect())
+            .collect()
+    }
+
+    fn roundtrip<T: Sample + PartialEq + std::fmt::Debug>(planes: &[Vec<T>]) {
+        let refs: Vec<&[T]> = planes.iter().map(|p| &p[..]).collect();
+        let channels = planes.len();
+        let mut samples = vec![T::default(); 13 * channels];
+        interleave(&refs, &mut samples);
+        for (i, s) in samples.iter().enumerate() {
+            assert_eq!(*s, planes[i % channels][i / channels]);
+        }
+
+        let mut split = vec![vec![T::default(); 13]; channels];
+        let mut refs: Vec<&mut [T]> = split.iter_mut().map(|p| &mut p[..]).collect();
+        deinterleave(&samples, &mut refs);
+        assert_eq!(split, planes);
+    }
+
+    #[test]
+    fn interleaving() {
+        for channels in 1..4 {
+            let planes = planes(channels);
+            roundtrip(&planes);
+
+            let planes: Vec<Vec<f32>> = planes
+                .iter()
+                .map(|p| p.iter().map(|&s| s as f32).collect())
+                .collect();
+            roundtrip(&planes);
+        }
+
+        roundtrip(&[vec![i16::MIN; 13], vec![i16::MAX; 13]]);
+    }
+}